@@ -27,6 +27,10 @@ export default function BondingCurveChart({ curveType, progress }: BondingCurveC
         case 'logarithmic':
           y = x < 0.01 ? 0 : Math.log(1 + x * 9) / Math.log(10)
           break
+        case 'constantproduct':
+          // Virtual reserves for a 70% sell split: price ends at (7/3)^2 times the start
+          y = (1 / Math.pow(1 - x * 4 / 7, 2) - 1) / (49 / 9 - 1)
+          break
         default:
           y = x
       }
@@ -16,7 +16,7 @@ export default function CreateLaunchModal({ onClose }: CreateLaunchModalProps) {
     symbol: '',
     uri: '',
     supply: '',
-    sellPercentage: '70',
+    sellPercentage: '52',
     targetSOL: '85',
     curveType: 'linear',
     migrateType: 'cpmm',
@@ -157,6 +157,7 @@ export default function CreateLaunchModal({ onClose }: CreateLaunchModalProps) {
                   <option value="linear">Linear</option>
                   <option value="exponential">Exponential</option>
                   <option value="logarithmic">Logarithmic</option>
+                  <option value="constantproduct">Constant Product</option>
                 </select>
               </div>
 
@@ -30,8 +30,9 @@ export const useLaunchpad = () => {
       // In production, this would:
       // 1. Load the program IDL
       // 2. Create an Anchor provider
-      // 3. Call the create_launch instruction
-      // 4. Sign and send the transaction
+      // 3. Generate the mint keypair and derive the launch PDA from its public key
+      // 4. Call the create_launch instruction
+      // 5. Sign and send the transaction with the wallet and the mint keypair
 
       console.log('Creating launch with params:', params)
 
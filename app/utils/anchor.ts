@@ -23,6 +23,7 @@ export const getConfigPDA = () => {
   )
 }
 
+// `mint` is the public key of the keypair that signed create_launch
 export const getLaunchPDA = (mint: PublicKey) => {
   return PublicKey.findProgramAddressSync(
     [Buffer.from('launch'), mint.toBuffer()],
@@ -30,9 +31,9 @@ export const getLaunchPDA = (mint: PublicKey) => {
   )
 }
 
-export const getMintPDA = (creator: PublicKey, name: string) => {
+export const getPiecewiseCurvePDA = (launch: PublicKey) => {
   return PublicKey.findProgramAddressSync(
-    [Buffer.from('mint'), creator.toBuffer(), Buffer.from(name)],
+    [Buffer.from('curve'), launch.toBuffer()],
     PROGRAM_ID
   )
 }
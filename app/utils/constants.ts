@@ -23,6 +23,8 @@ export enum CurveType {
   Linear,
   Exponential,
   Logarithmic,
+  ConstantProduct,
+  Piecewise,
 }
 
 export enum MigrateType {
@@ -34,4 +36,6 @@ export enum LaunchStatus {
   Active,
   Migrated,
   Cancelled,
+  Completed,
+  Failed,
 }
@@ -64,4 +64,79 @@ pub enum LaunchpadError {
 
     #[msg("Vesting period not ended")]
     VestingNotEnded,
+
+    #[msg("Invalid piecewise curve points")]
+    InvalidCurvePoints,
+
+    #[msg("Piecewise curve account required")]
+    MissingCurvePoints,
+
+    #[msg("Invalid launch limits")]
+    InvalidLaunchLimits,
+
+    #[msg("Token decimals not allowed")]
+    InvalidDecimals,
+
+    #[msg("Launch has already been traded")]
+    LaunchAlreadyTraded,
+
+    #[msg("Launch is not cancelled or failed")]
+    LaunchNotCancelled,
+
+    #[msg("No refund to claim")]
+    NoRefundToClaim,
+
+    #[msg("Deadline must be after the start time")]
+    InvalidDeadline,
+
+    #[msg("Launch deadline has passed")]
+    DeadlinePassed,
+
+    #[msg("Start time must not be in the past")]
+    InvalidStartTime,
+
+    #[msg("Launch has not started yet")]
+    LaunchNotStarted,
+
+    #[msg("Invalid presale configuration")]
+    InvalidPresale,
+
+    #[msg("Buyer not on the presale allowlist")]
+    NotAllowlisted,
+
+    #[msg("Presale allocation exceeded")]
+    PresaleAllocationExceeded,
+
+    #[msg("Invalid purchase caps")]
+    InvalidPurchaseCaps,
+
+    #[msg("Purchase cap exceeded")]
+    PurchaseCapExceeded,
+
+    #[msg("Invalid launch fee schedule")]
+    InvalidLaunchFee,
+
+    #[msg("Invalid vesting schedule")]
+    InvalidVestingSchedule,
+
+    #[msg("Invalid allocations")]
+    InvalidAllocations,
+
+    #[msg("Allocation not found")]
+    AllocationNotFound,
+
+    #[msg("Metadata accounts do not match the token program")]
+    InvalidMetadataAccounts,
+
+    #[msg("Launch metadata is locked after migration")]
+    MetadataLocked,
+
+    #[msg("Launch has not missed its deadline")]
+    LaunchNotExpired,
+
+    #[msg("Vesting vault is empty")]
+    VestingVaultEmpty,
+
+    #[msg("Supply left after the curve can't fund the migration pool at the curve's price")]
+    PoolUnderfunded,
 }
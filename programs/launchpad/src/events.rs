@@ -0,0 +1,119 @@
+use anchor_lang::prelude::*;
+use crate::state::LaunchLimits;
+
+/// Emitted when a buy is capped at the tokens left on the curve
+#[event]
+pub struct BuyPartiallyFilled {
+    pub launch: Pubkey,
+    pub buyer: Pubkey,
+    pub tokens_filled: u64,
+    pub sol_charged: u64,
+    pub sol_refunded: u64,
+}
+
+/// Emitted when the config authority changes the trading fee
+#[event]
+pub struct FeePercentageUpdated {
+    pub old_fee_percentage: u16,
+    pub new_fee_percentage: u16,
+}
+
+/// Emitted when the config authority changes the referral fee
+#[event]
+pub struct ReferralFeeUpdated {
+    pub old_referral_fee: u16,
+    pub new_referral_fee: u16,
+}
+
+/// Emitted when the config authority points community fees at a new account
+#[event]
+pub struct CommunityPoolUpdated {
+    pub old_community_pool: Pubkey,
+    pub new_community_pool: Pubkey,
+}
+
+/// Emitted when the config authority changes the bounds on new launches
+#[event]
+pub struct LaunchLimitsUpdated {
+    pub limits: LaunchLimits,
+}
+
+/// Emitted when the config authority proposes a successor, default to withdraw the proposal
+#[event]
+pub struct AuthorityProposed {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+}
+
+/// Emitted when a proposed authority accepts and takes over the config
+#[event]
+pub struct AuthorityTransferred {
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+/// Emitted when the creator changes a launch's name, symbol or uri
+#[event]
+pub struct LaunchMetadataUpdated {
+    pub launch: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+/// Emitted when a launch creator proposes a successor, default to withdraw the proposal
+#[event]
+pub struct CreatorProposed {
+    pub launch: Pubkey,
+    pub creator: Pubkey,
+    pub pending_creator: Pubkey,
+}
+
+/// Emitted when a proposed creator accepts and takes over the launch
+#[event]
+pub struct CreatorTransferred {
+    pub launch: Pubkey,
+    pub old_creator: Pubkey,
+    pub new_creator: Pubkey,
+}
+
+/// Emitted when a launch is cancelled and opens for refunds
+#[event]
+pub struct LaunchCancelled {
+    pub launch: Pubkey,
+    pub cancelled_by: Pubkey,
+    pub tokens_outstanding: u64,
+    pub sol_reserve: u64,
+}
+
+/// Emitted when a launch misses its deadline and opens for refunds
+#[event]
+pub struct LaunchFailed {
+    pub launch: Pubkey,
+    pub sol_raised: u64,
+    pub total_fund_raising: u64,
+}
+
+/// Emitted when a holder returns tokens to a cancelled or failed launch
+#[event]
+pub struct RefundClaimed {
+    pub launch: Pubkey,
+    pub holder: Pubkey,
+    pub tokens_returned: u64,
+    pub sol_refunded: u64,
+}
+
+/// Emitted when the vesting vault of a cancelled or failed launch is burned
+#[event]
+pub struct VestingBurned {
+    pub launch: Pubkey,
+    pub amount: u64,
+}
+
+/// Emitted when an allocation bucket's vault of a cancelled or failed launch is burned
+#[event]
+pub struct AllocationBurned {
+    pub launch: Pubkey,
+    pub index: u8,
+    pub amount: u64,
+}
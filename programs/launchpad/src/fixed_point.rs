@@ -0,0 +1,312 @@
+//! Q64.64 fixed-point math used by the bonding curves
+//!
+//! Values are unsigned 128-bit integers with 64 fractional bits. Products and quotients go
+//! through a 256-bit intermediate so nothing wraps; every operation is checked and returns
+//! `None` instead of saturating, and lossy operations take an explicit `Rounding`.
+
+pub use u256::U256;
+
+// The macro expansion trips lints we cannot fix from here
+#[allow(clippy::manual_div_ceil, clippy::assign_op_pattern)]
+mod u256 {
+    use uint::construct_uint;
+
+    construct_uint! {
+        /// 256-bit unsigned integer used for intermediate products
+        pub struct U256(4);
+    }
+}
+
+/// Number of fractional bits
+pub const RESOLUTION: u32 = 64;
+
+/// Rounding direction for lossy operations
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+/// Calculate `a * b / denominator` with a 256-bit intermediate, or `None` on a zero
+/// denominator or a result that does not fit in 128 bits
+pub fn mul_div(a: u128, b: u128, denominator: u128, rounding: Rounding) -> Option<u128> {
+    if denominator == 0 {
+        return None;
+    }
+
+    let product = U256::from(a) * U256::from(b);
+    let denominator = U256::from(denominator);
+    let (mut quotient, remainder) = product.div_mod(denominator);
+
+    if rounding == Rounding::Up && !remainder.is_zero() {
+        quotient += U256::one();
+    }
+
+    u128::try_from(quotient).ok()
+}
+
+/// Unsigned Q64.64 fixed-point number
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Q64x64(u128);
+
+impl Q64x64 {
+    pub const ZERO: Self = Self(0);
+    pub const ONE: Self = Self(1 << RESOLUTION);
+
+    /// ln(2) rounded down
+    pub const LN_2: Self = Self(0xB172_17F7_D1CF_79AB);
+    /// ln(2) rounded up
+    pub const LN_2_UP: Self = Self(0xB172_17F7_D1CF_79AC);
+
+    /// Build from the raw 128-bit representation
+    pub const fn from_raw(raw: u128) -> Self {
+        Self(raw)
+    }
+
+    /// Raw 128-bit representation
+    pub const fn raw(self) -> u128 {
+        self.0
+    }
+
+    /// Build from an integer
+    pub fn from_int(value: u64) -> Self {
+        Self((value as u128) << RESOLUTION)
+    }
+
+    /// Build `numerator / denominator`
+    pub fn from_ratio(numerator: u128, denominator: u128, rounding: Rounding) -> Option<Self> {
+        mul_div(numerator, Self::ONE.0, denominator, rounding).map(Self)
+    }
+
+    /// Convert to an integer
+    pub fn to_int(self, rounding: Rounding) -> Option<u64> {
+        let whole = self.0 >> RESOLUTION;
+        let fraction = self.0 & (u64::MAX as u128);
+        let value = if rounding == Rounding::Up && fraction != 0 { whole + 1 } else { whole };
+        u64::try_from(value).ok()
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Self)
+    }
+
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.0.checked_sub(other.0).map(Self)
+    }
+
+    pub fn saturating_sub(self, other: Self) -> Self {
+        Self(self.0.saturating_sub(other.0))
+    }
+
+    /// Multiply, exact to within one unit in the last place in the `rounding` direction
+    pub fn checked_mul(self, other: Self, rounding: Rounding) -> Option<Self> {
+        mul_div(self.0, other.0, Self::ONE.0, rounding).map(Self)
+    }
+
+    /// Divide, exact to within one unit in the last place in the `rounding` direction
+    pub fn checked_div(self, other: Self, rounding: Rounding) -> Option<Self> {
+        mul_div(self.0, Self::ONE.0, other.0, rounding).map(Self)
+    }
+
+    /// Multiply an integer amount by this value, returning an integer
+    pub fn mul_int(self, value: u128, rounding: Rounding) -> Option<u128> {
+        mul_div(value, self.0, Self::ONE.0, rounding)
+    }
+
+    /// Divide by an integer
+    pub fn div_int(self, value: u128, rounding: Rounding) -> Option<Self> {
+        mul_div(self.0, 1, value, rounding).map(Self)
+    }
+
+    /// Raise to an integer power by repeated squaring
+    ///
+    /// Every intermediate product is rounded in the `rounding` direction, so the result is a
+    /// bound on the exact power; it is off by at most `2 * log2(exponent)` roundings.
+    pub fn pow(self, mut exponent: u32, rounding: Rounding) -> Option<Self> {
+        let mut base = self;
+        let mut result = Self::ONE;
+
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.checked_mul(base, rounding)?;
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = base.checked_mul(base, rounding)?;
+            }
+        }
+
+        Some(result)
+    }
+
+    /// Square root, exact to within one unit in the last place in the `rounding` direction
+    pub fn sqrt(self, rounding: Rounding) -> Option<Self> {
+        // sqrt(x * 2^64) * 2^32 = sqrt(x) * 2^64
+        let scaled = U256::from(self.0) << RESOLUTION;
+        let mut root = scaled.integer_sqrt();
+
+        if rounding == Rounding::Up && root * root < scaled {
+            root += U256::one();
+        }
+
+        u128::try_from(root).ok().map(Self)
+    }
+
+    /// Natural logarithm of a value of at least one, rounded down
+    ///
+    /// Computes log2 bit by bit (integer part from the leading bit, then 64 fraction bits by
+    /// repeated squaring) and scales by ln(2). Every step rounds down, so the result never
+    /// exceeds ln(x) and is below it by less than 2^-58. Returns `None` for values below one.
+    pub fn ln(self) -> Option<Self> {
+        if self < Self::ONE {
+            return None;
+        }
+
+        // Integer part of log2 and the mantissa in [1, 2)
+        let integer = (127 - self.0.leading_zeros()) - RESOLUTION;
+        let mut mantissa = self.0 >> integer;
+
+        let mut log2 = (integer as u128) << RESOLUTION;
+        let two = 2u128 << RESOLUTION;
+        for bit in (0..RESOLUTION).rev() {
+            mantissa = mul_div(mantissa, mantissa, Self::ONE.0, Rounding::Down)?;
+            if mantissa >= two {
+                mantissa >>= 1;
+                log2 |= 1 << bit;
+            }
+        }
+
+        Self(log2).checked_mul(Self::LN_2, Rounding::Down)
+    }
+
+    /// Exponential function, rounded down
+    ///
+    /// Splits x = k * ln(2) + r with 0 <= r < ln(2) and sums the Taylor series of e^r until
+    /// terms vanish, then shifts by k. Every step rounds down, so the result never exceeds
+    /// e^x and is below it by a relative error of less than 2^-58. Returns `None` when the
+    /// result does not fit (x above roughly 44).
+    pub fn exp(self) -> Option<Self> {
+        // Dividing by ln(2) rounded up keeps k * ln(2) at or below x, so r is never too large
+        let k = (self.checked_div(Self::LN_2_UP, Rounding::Down)?.0 >> RESOLUTION) as u32;
+        if k >= 128 - RESOLUTION {
+            return None;
+        }
+
+        let remainder = self.checked_sub(Self(Self::LN_2_UP.0 * k as u128))?;
+
+        let mut sum = Self::ONE;
+        let mut term = Self::ONE;
+        let mut n = 1u128;
+        while term > Self::ZERO {
+            term = term
+                .checked_mul(remainder, Rounding::Down)?
+                .div_int(n, Rounding::Down)?;
+            sum = sum.checked_add(term)?;
+            n += 1;
+        }
+
+        sum.0.checked_shl(k).filter(|raw| raw >> k == sum.0).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_f64(value: Q64x64) -> f64 {
+        value.raw() as f64 / Q64x64::ONE.raw() as f64
+    }
+
+    fn from_f64(value: f64) -> Q64x64 {
+        Q64x64::from_raw((value * Q64x64::ONE.raw() as f64) as u128)
+    }
+
+    #[test]
+    fn test_mul_div_rounding() {
+        assert_eq!(mul_div(10, 10, 3, Rounding::Down), Some(33));
+        assert_eq!(mul_div(10, 10, 3, Rounding::Up), Some(34));
+        assert_eq!(mul_div(9, 10, 3, Rounding::Up), Some(30));
+        assert_eq!(mul_div(u128::MAX, u128::MAX, u128::MAX, Rounding::Down), Some(u128::MAX));
+        assert_eq!(mul_div(u128::MAX, 2, 1, Rounding::Down), None);
+        assert_eq!(mul_div(1, 1, 0, Rounding::Down), None);
+    }
+
+    #[test]
+    fn test_ratio_and_int_conversion() {
+        let third = Q64x64::from_ratio(1, 3, Rounding::Down).unwrap();
+        let third_up = Q64x64::from_ratio(1, 3, Rounding::Up).unwrap();
+        assert_eq!(third_up.raw() - third.raw(), 1);
+
+        assert_eq!(Q64x64::from_int(7).to_int(Rounding::Down), Some(7));
+        assert_eq!(Q64x64::from_ratio(7, 2, Rounding::Down).unwrap().to_int(Rounding::Down), Some(3));
+        assert_eq!(Q64x64::from_ratio(7, 2, Rounding::Down).unwrap().to_int(Rounding::Up), Some(4));
+        assert_eq!(Q64x64::from_raw(u128::MAX).to_int(Rounding::Up), None);
+    }
+
+    #[test]
+    fn test_mul_and_div() {
+        let a = Q64x64::from_ratio(3, 2, Rounding::Down).unwrap();
+        let b = Q64x64::from_int(4);
+        assert_eq!(a.checked_mul(b, Rounding::Down), Some(Q64x64::from_int(6)));
+        assert_eq!(b.checked_div(a, Rounding::Down).unwrap().to_int(Rounding::Down), Some(2));
+        assert_eq!(b.checked_div(Q64x64::ZERO, Rounding::Down), None);
+        assert_eq!(Q64x64::from_int(u64::MAX).checked_mul(b, Rounding::Down), None);
+        assert_eq!(a.mul_int(1_000_000_000, Rounding::Down), Some(1_500_000_000));
+    }
+
+    #[test]
+    fn test_pow() {
+        let a = Q64x64::from_ratio(3, 2, Rounding::Down).unwrap();
+        assert_eq!(a.pow(0, Rounding::Down), Some(Q64x64::ONE));
+        assert_eq!(a.pow(3, Rounding::Down), Some(Q64x64::from_ratio(27, 8, Rounding::Down).unwrap()));
+        assert_eq!(Q64x64::from_int(2).pow(63, Rounding::Down), Some(Q64x64::from_int(1 << 63)));
+        assert_eq!(Q64x64::from_int(2).pow(64, Rounding::Down), None);
+
+        let third = Q64x64::from_ratio(1, 3, Rounding::Down).unwrap();
+        assert!(third.pow(5, Rounding::Down).unwrap() <= third.pow(5, Rounding::Up).unwrap());
+    }
+
+    #[test]
+    fn test_sqrt() {
+        assert_eq!(Q64x64::from_int(16).sqrt(Rounding::Down), Some(Q64x64::from_int(4)));
+        assert_eq!(Q64x64::from_int(16).sqrt(Rounding::Up), Some(Q64x64::from_int(4)));
+
+        let two = Q64x64::from_int(2);
+        let down = two.sqrt(Rounding::Down).unwrap();
+        let up = two.sqrt(Rounding::Up).unwrap();
+        assert_eq!(up.raw() - down.raw(), 1);
+        assert!(down.checked_mul(down, Rounding::Up).unwrap() <= two);
+        assert!(up.checked_mul(up, Rounding::Down).unwrap() >= two);
+    }
+
+    #[test]
+    fn test_ln() {
+        assert_eq!(Q64x64::ONE.ln(), Some(Q64x64::ZERO));
+        assert_eq!(Q64x64::from_ratio(1, 2, Rounding::Down).unwrap().ln(), None);
+
+        let two = Q64x64::from_int(2).ln().unwrap();
+        assert!(two <= Q64x64::LN_2 && Q64x64::LN_2.raw() - two.raw() < 1 << 6);
+
+        for x in [1.000001f64, 1.5, std::f64::consts::E, 10.0, 12345.678, 1e15] {
+            let ln = from_f64(x).ln().unwrap();
+            assert!((to_f64(ln) - x.ln()).abs() < 1e-12, "ln({})", x);
+        }
+    }
+
+    #[test]
+    fn test_exp() {
+        assert_eq!(Q64x64::ZERO.exp(), Some(Q64x64::ONE));
+        assert_eq!(Q64x64::from_int(45).exp(), None);
+
+        for x in [0.000001f64, 0.5, 1.0, 2.5, 10.0, 30.0] {
+            let exp = from_f64(x).exp().unwrap();
+            let relative = (to_f64(exp) - x.exp()).abs() / x.exp();
+            assert!(relative < 1e-12, "exp({})", x);
+        }
+
+        // ln and exp round-trip within the documented bounds
+        let x = Q64x64::from_int(5);
+        let round_trip = x.ln().unwrap().exp().unwrap();
+        assert!(round_trip <= x && x.raw() - round_trip.raw() < 1 << 12);
+    }
+}
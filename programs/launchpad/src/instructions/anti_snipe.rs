@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+
+#[derive(Accounts)]
+pub struct ConfigureAntiSnipe<'info> {
+    #[account(
+        mut,
+        seeds = [b"launch", launch.mint.as_ref()],
+        bump = launch.bump,
+        has_one = creator,
+        constraint = launch.status == LaunchStatus::Active @ LaunchpadError::LaunchNotActive
+    )]
+    pub launch: Account<'info, Launch>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, LaunchpadConfig>,
+
+    pub creator: Signer<'info>,
+}
+
+pub fn configure_purchase_caps(
+    ctx: Context<ConfigureAntiSnipe>,
+    max_tokens_per_tx: u64,
+    max_tokens_per_wallet: u64,
+    duration: i64,
+) -> Result<()> {
+    let launch = &mut ctx.accounts.launch;
+
+    // Caps are part of the launch terms, fixed once trading starts
+    require!(!launch.has_traded, LaunchpadError::LaunchAlreadyTraded);
+    require!(duration >= 0, LaunchpadError::InvalidPurchaseCaps);
+    if max_tokens_per_tx > 0 && max_tokens_per_wallet > 0 {
+        require!(max_tokens_per_tx <= max_tokens_per_wallet, LaunchpadError::InvalidPurchaseCaps);
+    }
+
+    launch.max_tokens_per_tx = max_tokens_per_tx;
+    launch.max_tokens_per_wallet = max_tokens_per_wallet;
+    launch.purchase_caps_duration = duration;
+
+    msg!("Purchase caps set: {} per tx, {} per wallet", max_tokens_per_tx, max_tokens_per_wallet);
+
+    Ok(())
+}
+
+pub fn configure_launch_fee(ctx: Context<ConfigureAntiSnipe>, launch_fee_bps: u16, duration: i64) -> Result<()> {
+    let launch = &mut ctx.accounts.launch;
+
+    require!(!launch.has_traded, LaunchpadError::LaunchAlreadyTraded);
+    require!(launch_fee_bps <= Launch::MAX_LAUNCH_FEE_BPS, LaunchpadError::InvalidLaunchFee);
+    require!((0..=Launch::MAX_LAUNCH_FEE_DURATION).contains(&duration), LaunchpadError::InvalidLaunchFee);
+    // A fee above the base needs a window to decay over, or it would never apply
+    if launch_fee_bps > ctx.accounts.config.fee_percentage {
+        require!(duration > 0, LaunchpadError::InvalidLaunchFee);
+    }
+
+    launch.launch_fee_bps = launch_fee_bps;
+    launch.launch_fee_duration = duration;
+
+    msg!("Launch fee starts at {}% and decays over {}s", launch_fee_bps as f64 / 100.0, duration);
+
+    Ok(())
+}
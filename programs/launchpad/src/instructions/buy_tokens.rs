@@ -1,9 +1,12 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 use anchor_spl::associated_token::AssociatedToken;
 use crate::state::*;
 use crate::errors::*;
+use crate::events::*;
+use crate::utils::{allowlist_leaf, verify_merkle_proof};
+use crate::fixed_point::{mul_div, Rounding};
 
 #[derive(Accounts)]
 pub struct BuyTokens<'info> {
@@ -15,12 +18,20 @@ pub struct BuyTokens<'info> {
     )]
     pub launch: Account<'info, Launch>,
 
+    #[account(address = launch.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
     #[account(
         mut,
-        associated_token::mint = launch.mint,
+        associated_token::mint = mint,
         associated_token::authority = launch,
+        associated_token::token_program = token_program,
     )]
-    pub launch_token_account: Account<'info, TokenAccount>,
+    pub launch_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Breakpoints for Piecewise launches
+    #[account(has_one = launch)]
+    pub piecewise_curve: Option<Account<'info, PiecewiseCurve>>,
 
     #[account(
         init_if_needed,
@@ -34,10 +45,11 @@ pub struct BuyTokens<'info> {
     #[account(
         init_if_needed,
         payer = buyer,
-        associated_token::mint = launch.mint,
+        associated_token::mint = mint,
         associated_token::authority = buyer,
+        associated_token::token_program = token_program,
     )]
-    pub buyer_token_account: Account<'info, TokenAccount>,
+    pub buyer_token_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(mut)]
     pub buyer: Signer<'info>,
@@ -49,125 +61,312 @@ pub struct BuyTokens<'info> {
     pub config: Account<'info, LaunchpadConfig>,
 
     /// CHECK: Community pool receiving fees
-    #[account(mut)]
+    #[account(mut, address = config.community_pool)]
     pub community_pool: AccountInfo<'info>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
 }
 
-pub fn buy_tokens(ctx: Context<BuyTokens>, sol_amount: u64, min_tokens_out: u64) -> Result<()> {
-    let launch = &mut ctx.accounts.launch;
+pub fn buy_tokens(
+    ctx: Context<BuyTokens>,
+    sol_amount: u64,
+    min_tokens_out: u64,
+    presale_proof: Option<PresaleProof>,
+) -> Result<()> {
+    let launch = &ctx.accounts.launch;
+    let curve_points = ctx.accounts.piecewise_curve
+        .as_ref()
+        .map_or(&[][..], |curve| curve.points.as_slice());
 
     require!(sol_amount > 0, LaunchpadError::AmountTooSmall);
 
-    // Calculate tokens to receive based on bonding curve
-    let tokens_to_receive = launch.calculate_tokens_for_sol(sol_amount)?;
+    // Calculate fees, including any anti-sniper launch fee still decaying
+    let fee_percentage = launch.fee_bps_at(ctx.accounts.config.fee_percentage, Clock::get()?.unix_timestamp);
+    let fee_amount = sol_amount
+        .checked_mul(fee_percentage as u64)
+        .ok_or(LaunchpadError::ArithmeticOverflow)?
+        .checked_div(10000)
+        .ok_or(LaunchpadError::ArithmeticOverflow)?;
 
-    // Check slippage
-    require!(tokens_to_receive >= min_tokens_out, LaunchpadError::SlippageExceeded);
+    let net_sol = sol_amount
+        .checked_sub(fee_amount)
+        .ok_or(LaunchpadError::ArithmeticUnderflow)?;
 
-    // Check if enough tokens available
-    let remaining_tokens = launch.total_sell_amount
+    let remaining = launch.total_sell_amount
         .checked_sub(launch.tokens_sold)
         .ok_or(LaunchpadError::ArithmeticUnderflow)?;
+    let remaining_cost = launch.calculate_buy_cost(remaining, curve_points)?;
 
-    require!(tokens_to_receive <= remaining_tokens, LaunchpadError::InsufficientTokens);
+    if net_sol > remaining_cost {
+        // Final buy: fill only the tokens left and charge for those, the rest of
+        // sol_amount never leaves the buyer
+        require!(remaining > 0, LaunchpadError::InsufficientTokens);
+        require!(remaining >= min_tokens_out, LaunchpadError::SlippageExceeded);
 
-    // Calculate fees (1% total)
-    let fee_amount = sol_amount
-        .checked_mul(ctx.accounts.config.fee_percentage as u64)
-        .ok_or(LaunchpadError::ArithmeticOverflow)?
-        .checked_div(10000)
+        let (charged, fee_amount) = gross_up(remaining_cost, fee_percentage)?;
+
+        emit!(BuyPartiallyFilled {
+            launch: launch.key(),
+            buyer: ctx.accounts.buyer.key(),
+            tokens_filled: remaining,
+            sol_charged: charged,
+            sol_refunded: sol_amount - charged,
+        });
+        msg!("Partially filled: {} SOL unused", (sol_amount - charged) as f64 / 1_000_000_000.0);
+
+        let position_bump = ctx.bumps.user_position;
+        return ctx.accounts.settle_buy(position_bump, remaining, remaining_cost, fee_amount, presale_proof.as_ref());
+    }
+
+    // Calculate tokens to receive by integrating the bonding curve over the purchase
+    let tokens_to_receive = launch.calculate_tokens_for_sol(net_sol, curve_points)?;
+
+    require!(tokens_to_receive > 0, LaunchpadError::AmountTooSmall);
+
+    // Check slippage
+    require!(tokens_to_receive >= min_tokens_out, LaunchpadError::SlippageExceeded);
+
+    let position_bump = ctx.bumps.user_position;
+    ctx.accounts.settle_buy(position_bump, tokens_to_receive, net_sol, fee_amount, presale_proof.as_ref())
+}
+
+pub fn buy_tokens_exact_out(
+    ctx: Context<BuyTokens>,
+    token_amount: u64,
+    max_sol_in: u64,
+    presale_proof: Option<PresaleProof>,
+) -> Result<()> {
+    let launch = &ctx.accounts.launch;
+    let curve_points = ctx.accounts.piecewise_curve
+        .as_ref()
+        .map_or(&[][..], |curve| curve.points.as_slice());
+
+    require!(token_amount > 0, LaunchpadError::AmountTooSmall);
+
+    // Calculate the curve cost of exactly token_amount tokens; fails with
+    // InsufficientTokens if that is more than the curve has left to sell
+    let net_sol = launch.calculate_buy_cost(token_amount, curve_points)?;
+
+    require!(net_sol > 0, LaunchpadError::AmountTooSmall);
+
+    let fee_percentage = launch.fee_bps_at(ctx.accounts.config.fee_percentage, Clock::get()?.unix_timestamp);
+    let (sol_amount, fee_amount) = gross_up(net_sol, fee_percentage)?;
+
+    // Check slippage
+    require!(sol_amount <= max_sol_in, LaunchpadError::SlippageExceeded);
+
+    let position_bump = ctx.bumps.user_position;
+    ctx.accounts.settle_buy(position_bump, token_amount, net_sol, fee_amount, presale_proof.as_ref())
+}
+
+/// Total to charge, and the fee part of it, for `net_sol` to reach the curve, so the fee is
+/// fee_percentage of the total paid as in buy_tokens
+fn gross_up(net_sol: u64, fee_percentage: u16) -> Result<(u64, u64)> {
+    let sol_amount = mul_div(net_sol as u128, 10000, 10000 - fee_percentage as u128, Rounding::Up)
+        .and_then(|amount| u64::try_from(amount).ok())
         .ok_or(LaunchpadError::ArithmeticOverflow)?;
 
-    let creator_fee = fee_amount / 2; // 50% to creator
-    let community_fee = fee_amount - creator_fee; // 50% to community pool
+    Ok((sol_amount, sol_amount - net_sol))
+}
 
-    let net_sol = sol_amount
-        .checked_sub(fee_amount)
-        .ok_or(LaunchpadError::ArithmeticUnderflow)?;
+impl<'info> BuyTokens<'info> {
+    /// Check the buyer's presale proof and that `tokens_to_receive` fits their allocation
+    fn check_allowlisted(&self, presale_proof: Option<&PresaleProof>, tokens_to_receive: u64) -> Result<()> {
+        let presale_proof = presale_proof.ok_or(LaunchpadError::NotAllowlisted)?;
+
+        let leaf = allowlist_leaf(&self.buyer.key(), presale_proof.allocation);
+        require!(
+            verify_merkle_proof(&presale_proof.proof, &self.launch.presale_merkle_root, leaf),
+            LaunchpadError::NotAllowlisted
+        );
+
+        // Presale is the first phase, so everything bought so far counts against the allocation
+        if presale_proof.allocation > 0 {
+            let bought = self.user_position.tokens_bought
+                .checked_add(tokens_to_receive)
+                .ok_or(LaunchpadError::ArithmeticOverflow)?;
+            require!(bought <= presale_proof.allocation, LaunchpadError::PresaleAllocationExceeded);
+        }
+
+        Ok(())
+    }
+
+    /// Check `tokens_to_receive` against the launch's per-transaction and per-wallet caps
+    fn check_purchase_caps(&self, tokens_to_receive: u64) -> Result<()> {
+        let launch = &self.launch;
+
+        if launch.max_tokens_per_tx > 0 {
+            require!(tokens_to_receive <= launch.max_tokens_per_tx, LaunchpadError::PurchaseCapExceeded);
+        }
+
+        if launch.max_tokens_per_wallet > 0 {
+            let held = self.user_position.net_tokens()
+                .checked_add(tokens_to_receive)
+                .ok_or(LaunchpadError::ArithmeticOverflow)?;
+            require!(held <= launch.max_tokens_per_wallet, LaunchpadError::PurchaseCapExceeded);
+        }
+
+        Ok(())
+    }
+
+    /// Collect `net_sol` into the curve and `fee_amount` as fees from the buyer, hand over
+    /// `tokens_to_receive` and record the trade on the launch and the buyer's position
+    fn settle_buy(
+        &mut self,
+        position_bump: u8,
+        tokens_to_receive: u64,
+        net_sol: u64,
+        fee_amount: u64,
+        presale_proof: Option<&PresaleProof>,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        self.launch.check_trading_open(now)?;
+        if self.launch.is_presale(now) {
+            self.check_allowlisted(presale_proof, tokens_to_receive)?;
+        }
+        if self.launch.purchase_caps_active(now) {
+            self.check_purchase_caps(tokens_to_receive)?;
+        }
+
+        let launch = &mut self.launch;
+
+        let creator_fee = fee_amount / 2; // 50% to creator
+        let community_fee = fee_amount - creator_fee; // 50% to community pool
+
+        let sol_amount = net_sol
+            .checked_add(fee_amount)
+            .ok_or(LaunchpadError::ArithmeticOverflow)?;
+
+        // Transfer SOL from buyer to launch, including the creator fee held until claimed
+        let launch_amount = net_sol
+            .checked_add(creator_fee)
+            .ok_or(LaunchpadError::ArithmeticOverflow)?;
 
-    // Transfer SOL from buyer to launch
-    system_program::transfer(
-        CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            system_program::Transfer {
-                from: ctx.accounts.buyer.to_account_info(),
-                to: launch.to_account_info(),
-            },
-        ),
-        net_sol,
-    )?;
-
-    // Transfer community fee
-    if community_fee > 0 {
         system_program::transfer(
             CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
+                self.system_program.to_account_info(),
                 system_program::Transfer {
-                    from: ctx.accounts.buyer.to_account_info(),
-                    to: ctx.accounts.community_pool.to_account_info(),
+                    from: self.buyer.to_account_info(),
+                    to: launch.to_account_info(),
                 },
             ),
-            community_fee,
+            launch_amount,
         )?;
-    }
 
-    // Transfer tokens from launch to buyer
-    let seeds = &[
-        b"launch",
-        launch.mint.as_ref(),
-        &[launch.bump],
-    ];
-    let signer = &[&seeds[..]];
-
-    token::transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.launch_token_account.to_account_info(),
-                to: ctx.accounts.buyer_token_account.to_account_info(),
-                authority: launch.to_account_info(),
-            },
-            signer,
-        ),
-        tokens_to_receive,
-    )?;
-
-    // Update launch state
-    launch.tokens_sold = launch.tokens_sold
-        .checked_add(tokens_to_receive)
-        .ok_or(LaunchpadError::ArithmeticOverflow)?;
+        // Transfer community fee
+        if community_fee > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: self.buyer.to_account_info(),
+                        to: self.community_pool.to_account_info(),
+                    },
+                ),
+                community_fee,
+            )?;
+        }
 
-    launch.sol_raised = launch.sol_raised
-        .checked_add(net_sol)
-        .ok_or(LaunchpadError::ArithmeticOverflow)?;
+        // Transfer tokens from launch to buyer
+        let seeds = &[
+            b"launch",
+            launch.mint.as_ref(),
+            &[launch.bump],
+        ];
+        let signer = &[&seeds[..]];
 
-    launch.creator_fee_earned = launch.creator_fee_earned
-        .checked_add(creator_fee)
-        .ok_or(LaunchpadError::ArithmeticOverflow)?;
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.launch_token_account.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    to: self.buyer_token_account.to_account_info(),
+                    authority: launch.to_account_info(),
+                },
+                signer,
+            ),
+            tokens_to_receive,
+            self.mint.decimals,
+        )?;
+
+        // Update launch state
+        launch.has_traded = true;
+        launch.tokens_sold = launch.tokens_sold
+            .checked_add(tokens_to_receive)
+            .ok_or(LaunchpadError::ArithmeticOverflow)?;
+
+        launch.sol_raised = launch.sol_raised
+            .checked_add(net_sol)
+            .ok_or(LaunchpadError::ArithmeticOverflow)?;
+
+        launch.sol_in_curve = launch.sol_in_curve
+            .checked_add(net_sol)
+            .ok_or(LaunchpadError::ArithmeticOverflow)?;
+
+        launch.creator_fee_earned = launch.creator_fee_earned
+            .checked_add(creator_fee)
+            .ok_or(LaunchpadError::ArithmeticOverflow)?;
+
+        // Update user position
+        let position = &mut self.user_position;
+        if position.user == Pubkey::default() {
+            position.user = self.buyer.key();
+            position.launch = launch.key();
+            position.bump = position_bump;
+        }
+
+        position.tokens_bought = position.tokens_bought
+            .checked_add(tokens_to_receive)
+            .ok_or(LaunchpadError::ArithmeticOverflow)?;
 
-    // Update user position
-    let position = &mut ctx.accounts.user_position;
-    if position.user == Pubkey::default() {
-        position.user = ctx.accounts.buyer.key();
-        position.launch = launch.key();
-        position.bump = ctx.bumps.user_position;
+        position.sol_spent = position.sol_spent
+            .checked_add(sol_amount)
+            .ok_or(LaunchpadError::ArithmeticOverflow)?;
+
+        position.sol_in_curve = position.sol_in_curve
+            .checked_add(net_sol)
+            .ok_or(LaunchpadError::ArithmeticOverflow)?;
+
+        msg!("Bought {} tokens for {} SOL", tokens_to_receive, sol_amount as f64 / 1_000_000_000.0);
+        msg!("Progress: {}%", launch.get_progress());
+
+        // Freeze the curve once it is done so the price holds until migration
+        if launch.is_complete() {
+            launch.status = LaunchStatus::Completed;
+            msg!("Bonding curve completed, ready to migrate");
+        }
+
+        Ok(())
     }
+}
 
-    position.tokens_bought = position.tokens_bought
-        .checked_add(tokens_to_receive)
-        .ok_or(LaunchpadError::ArithmeticOverflow)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    position.sol_spent = position.sol_spent
-        .checked_add(sol_amount)
-        .ok_or(LaunchpadError::ArithmeticOverflow)?;
+    #[test]
+    fn test_gross_up_fee_boundaries() {
+        // Small and odd amounts are where the rounding bites
+        let amounts = [1, 2, 3, 7, 99, 101, 9_999, 10_001, 1_000_000_007, 85_000_000_000];
 
-    msg!("Bought {} tokens for {} SOL", tokens_to_receive, sol_amount as f64 / 1_000_000_000.0);
-    msg!("Progress: {}%", launch.get_progress());
+        for fee_bps in [0, 1, 100, 999, LaunchpadConfig::MAX_FEE_BPS, Launch::MAX_LAUNCH_FEE_BPS] {
+            for net_sol in amounts {
+                let (gross, fee) = gross_up(net_sol, fee_bps).unwrap();
+                assert_eq!(net_sol + fee, gross, "{} at {} bps", net_sol, fee_bps);
 
-    Ok(())
+                // Never below fee_bps of what is paid, nor more than one lamport above it
+                let fee_floor = gross as u128 * fee_bps as u128;
+                assert!(fee as u128 * 10000 >= fee_floor, "{} at {} bps", net_sol, fee_bps);
+                assert!(fee == 0 || (fee as u128 - 1) * 10000 < fee_floor, "{} at {} bps", net_sol, fee_bps);
+            }
+        }
+
+        assert_eq!(gross_up(85_000_000_000, 0).unwrap(), (85_000_000_000, 0));
+        assert_eq!(gross_up(1, Launch::MAX_LAUNCH_FEE_BPS).unwrap(), (10, 9));
+        assert!(gross_up(u64::MAX, 100).is_err());
+    }
 }
@@ -0,0 +1,206 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+use crate::state::*;
+use crate::errors::*;
+use crate::events::*;
+
+#[derive(Accounts)]
+pub struct CancelLaunch<'info> {
+    #[account(
+        mut,
+        seeds = [b"launch", launch.mint.as_ref()],
+        bump = launch.bump,
+        constraint = launch.status == LaunchStatus::Active
+            || launch.status == LaunchStatus::Completed @ LaunchpadError::LaunchNotActive
+    )]
+    pub launch: Account<'info, Launch>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, LaunchpadConfig>,
+
+    /// Launch creator or config authority
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FailLaunch<'info> {
+    #[account(
+        mut,
+        seeds = [b"launch", launch.mint.as_ref()],
+        bump = launch.bump,
+    )]
+    pub launch: Account<'info, Launch>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRefund<'info> {
+    #[account(
+        mut,
+        seeds = [b"launch", launch.mint.as_ref()],
+        bump = launch.bump,
+    )]
+    pub launch: Account<'info, Launch>,
+
+    #[account(address = launch.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = launch,
+        associated_token::token_program = token_program,
+    )]
+    pub launch_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"position", launch.key().as_ref(), holder.key().as_ref()],
+        bump = user_position.bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = holder,
+        associated_token::token_program = token_program,
+    )]
+    pub holder_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn cancel_launch(ctx: Context<CancelLaunch>) -> Result<()> {
+    let launch = &mut ctx.accounts.launch;
+    let authority = ctx.accounts.authority.key();
+
+    // A launch past its deadline has failed, and refunds on the failed basis
+    require!(!launch.is_expired(Clock::get()?.unix_timestamp), LaunchpadError::DeadlinePassed);
+
+    // The admin can unwind any launch before migration, the creator only an untraded one
+    if authority != ctx.accounts.config.authority {
+        require!(authority == launch.creator, LaunchpadError::InvalidAuthority);
+        require!(!launch.has_traded, LaunchpadError::LaunchAlreadyTraded);
+    }
+
+    launch.status = LaunchStatus::Cancelled;
+
+    emit!(LaunchCancelled {
+        launch: launch.key(),
+        cancelled_by: authority,
+        tokens_outstanding: launch.tokens_sold,
+        sol_reserve: launch.sol_raised,
+    });
+    msg!("Launch cancelled with {} SOL to refund", launch.sol_raised as f64 / 1_000_000_000.0);
+
+    Ok(())
+}
+
+pub fn fail_launch(ctx: Context<FailLaunch>) -> Result<()> {
+    let launch = &mut ctx.accounts.launch;
+
+    require!(launch.is_expired(Clock::get()?.unix_timestamp), LaunchpadError::LaunchNotExpired);
+    mark_failed(launch);
+
+    Ok(())
+}
+
+pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
+    let launch = &mut ctx.accounts.launch;
+    let position = &mut ctx.accounts.user_position;
+
+    // The first claim after a missed deadline moves the launch to Failed if nobody has yet
+    if launch.is_expired(Clock::get()?.unix_timestamp) {
+        mark_failed(launch);
+    }
+
+    // Refund what the holder bought and still holds, each token at most once
+    let token_amount = position.net_tokens().min(ctx.accounts.holder_token_account.amount);
+
+    require!(token_amount > 0, LaunchpadError::NoRefundToClaim);
+
+    let net_sol_refund = position.net_sol_refund(token_amount)?;
+    let refund = match launch.status {
+        // Failed launches hand back what each buyer put into the curve, scaled down if
+        // profitable sells left the reserve short of it
+        LaunchStatus::Failed => launch.net_refund_for(net_sol_refund)?,
+        LaunchStatus::Cancelled => launch.refund_for(token_amount)?,
+        _ => return err!(LaunchpadError::LaunchNotCancelled),
+    };
+
+    // Dust positions can round down to nothing, keep their tokens rather than take them for free
+    require!(refund > 0, LaunchpadError::AmountTooSmall);
+
+    // Transfer tokens from holder back to launch
+    token_interface::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.holder_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.launch_token_account.to_account_info(),
+                authority: ctx.accounts.holder.to_account_info(),
+            },
+        ),
+        token_amount,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    // Transfer the holder's share of the curve reserve
+    **launch.to_account_info().try_borrow_mut_lamports()? -= refund;
+    **ctx.accounts.holder.to_account_info().try_borrow_mut_lamports()? += refund;
+
+    // Update launch state
+    launch.tokens_sold = launch.tokens_sold
+        .checked_sub(token_amount)
+        .ok_or(LaunchpadError::ArithmeticUnderflow)?;
+
+    launch.sol_raised = launch.sol_raised
+        .checked_sub(refund)
+        .ok_or(LaunchpadError::ArithmeticUnderflow)?;
+
+    launch.sol_in_curve = launch.sol_in_curve
+        .checked_sub(net_sol_refund)
+        .ok_or(LaunchpadError::ArithmeticUnderflow)?;
+
+    // Record the returned tokens like a sale so they can't be refunded again
+    position.tokens_sold = position.tokens_sold
+        .checked_add(token_amount)
+        .ok_or(LaunchpadError::ArithmeticOverflow)?;
+
+    position.sol_received = position.sol_received
+        .checked_add(refund)
+        .ok_or(LaunchpadError::ArithmeticOverflow)?;
+
+    position.sol_in_curve = position.sol_in_curve
+        .checked_sub(net_sol_refund)
+        .ok_or(LaunchpadError::ArithmeticUnderflow)?;
+
+    emit!(RefundClaimed {
+        launch: launch.key(),
+        holder: ctx.accounts.holder.key(),
+        tokens_returned: token_amount,
+        sol_refunded: refund,
+    });
+    msg!("Refunded {} SOL for {} tokens", refund as f64 / 1_000_000_000.0, token_amount);
+
+    Ok(())
+}
+
+/// Move an Active launch past its deadline to Failed, opening it for refunds
+fn mark_failed(launch: &mut Account<Launch>) {
+    launch.status = LaunchStatus::Failed;
+
+    emit!(LaunchFailed {
+        launch: launch.key(),
+        sol_raised: launch.sol_raised,
+        total_fund_raising: launch.total_fund_raising,
+    });
+    msg!("Launch failed to reach its goal by the deadline");
+}
@@ -0,0 +1,116 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+use crate::events::*;
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority @ LaunchpadError::InvalidAuthority
+    )]
+    pub config: Account<'info, LaunchpadConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.pending_authority == new_authority.key() @ LaunchpadError::InvalidAuthority
+    )]
+    pub config: Account<'info, LaunchpadConfig>,
+
+    pub new_authority: Signer<'info>,
+}
+
+pub fn update_config(
+    ctx: Context<UpdateConfig>,
+    fee_percentage: Option<u16>,
+    community_pool: Option<Pubkey>,
+    referral_fee: Option<u16>,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    if let Some(fee_percentage) = fee_percentage {
+        require!(fee_percentage <= LaunchpadConfig::MAX_FEE_BPS, LaunchpadError::InvalidFeePercentage);
+
+        emit!(FeePercentageUpdated {
+            old_fee_percentage: config.fee_percentage,
+            new_fee_percentage: fee_percentage,
+        });
+        config.fee_percentage = fee_percentage;
+        msg!("Fee updated to {}%", fee_percentage as f64 / 100.0);
+    }
+
+    if let Some(community_pool) = community_pool {
+        emit!(CommunityPoolUpdated {
+            old_community_pool: config.community_pool,
+            new_community_pool: community_pool,
+        });
+        config.community_pool = community_pool;
+        msg!("Community pool updated to {}", community_pool);
+    }
+
+    if let Some(referral_fee) = referral_fee {
+        require!(referral_fee <= LaunchpadConfig::MAX_FEE_BPS, LaunchpadError::InvalidFeePercentage);
+
+        emit!(ReferralFeeUpdated {
+            old_referral_fee: config.referral_fee,
+            new_referral_fee: referral_fee,
+        });
+        config.referral_fee = referral_fee;
+        msg!("Referral fee updated to {}%", referral_fee as f64 / 100.0);
+    }
+
+    Ok(())
+}
+
+pub fn update_launch_limits(ctx: Context<UpdateConfig>, limits: LaunchLimits) -> Result<()> {
+    limits.validate()?;
+
+    ctx.accounts.config.limits = limits;
+
+    emit!(LaunchLimitsUpdated { limits });
+    msg!("Launch limits updated: sell {}-{} bps, raise {}-{} lamports, decimals {}-{}",
+        limits.min_sell_bps, limits.max_sell_bps,
+        limits.min_fund_raising, limits.max_fund_raising,
+        limits.min_decimals, limits.max_decimals);
+
+    Ok(())
+}
+
+/// Nominate the next config authority, which takes over once it signs accept_authority;
+/// proposing the default pubkey withdraws a pending proposal
+pub fn propose_authority(ctx: Context<UpdateConfig>, new_authority: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.pending_authority = new_authority;
+
+    emit!(AuthorityProposed {
+        authority: config.authority,
+        pending_authority: new_authority,
+    });
+    msg!("Authority transfer proposed to {}", new_authority);
+
+    Ok(())
+}
+
+pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let new_authority = ctx.accounts.new_authority.key();
+
+    emit!(AuthorityTransferred {
+        old_authority: config.authority,
+        new_authority,
+    });
+    config.authority = new_authority;
+    config.pending_authority = Pubkey::default();
+    msg!("Authority transferred to {}", new_authority);
+
+    Ok(())
+}
@@ -1,11 +1,19 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, MintTo};
-use anchor_spl::associated_token::AssociatedToken;
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_lang::system_program::{self, CreateAccount};
+use anchor_spl::token_2022::{self, spl_token_2022};
+use anchor_spl::token_2022::spl_token_2022::extension::{metadata_pointer, ExtensionType};
+use anchor_spl::token::{Mint, TokenAccount};
+use anchor_spl::token_2022::spl_token_2022::instruction::AuthorityType;
+use anchor_spl::token_interface::{self, InitializeAccount3, InitializeMint2, MintTo, SetAuthority, TokenInterface};
+use anchor_spl::associated_token::{self, get_associated_token_address_with_program_id, AssociatedToken, Create};
+use anchor_spl::metadata::{self, CreateMetadataAccountsV3, Metadata};
+use anchor_spl::metadata::mpl_token_metadata::types::DataV2;
+use spl_token_metadata_interface::state::TokenMetadata;
 use crate::state::*;
 use crate::errors::*;
 
 #[derive(Accounts)]
-#[instruction(name: String, symbol: String)]
 pub struct CreateLaunch<'info> {
     #[account(
         init,
@@ -16,66 +24,139 @@ pub struct CreateLaunch<'info> {
     )]
     pub launch: Account<'info, Launch>,
 
+    /// Fresh keypair chosen by the creator, so vanity addresses work. Created in the handler
+    /// under token_program, Token-2022 mints need their extensions initialized first
+    #[account(mut)]
+    pub mint: Signer<'info>,
+
+    /// CHECK: Launch's associated token account, created in the handler once the mint exists
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(&launch.key(), &mint.key(), &token_program.key())
+    )]
+    pub launch_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Holds the creator's share of supply until it vests, created in the handler
+    #[account(
+        mut,
+        seeds = [b"vesting", launch.key().as_ref()],
+        bump
+    )]
+    pub vesting_vault: UncheckedAccount<'info>,
+
+    /// Breakpoints for Piecewise launches, omitted for the other curve types
     #[account(
         init,
         payer = creator,
-        mint::decimals = 9,
-        mint::authority = launch,
-        seeds = [b"mint", creator.key().as_ref(), name.as_bytes()],
+        space = PiecewiseCurve::LEN,
+        seeds = [b"curve", launch.key().as_ref()],
         bump
     )]
-    pub mint: Account<'info, Mint>,
+    pub piecewise_curve: Option<Account<'info, PiecewiseCurve>>,
 
+    /// Allocation buckets, omitted when all non-curve supply vests to the creator
     #[account(
         init,
         payer = creator,
-        associated_token::mint = mint,
-        associated_token::authority = launch,
+        space = AllocationTable::LEN,
+        seeds = [b"allocations", launch.key().as_ref()],
+        bump
+    )]
+    pub allocation_table: Option<Account<'info, AllocationTable>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
     )]
-    pub launch_token_account: Account<'info, TokenAccount>,
+    pub config: Account<'info, LaunchpadConfig>,
 
     #[account(mut)]
     pub creator: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
+    /// CHECK: Metaplex metadata for SPL Token mints, omitted for Token-2022 mints which
+    /// carry their metadata on the mint
+    #[account(
+        mut,
+        seeds = [b"metadata", metadata::ID.as_ref(), mint.key().as_ref()],
+        bump,
+        seeds::program = metadata::ID
+    )]
+    pub metadata: Option<UncheckedAccount<'info>>,
+
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+    /// SPL Token or Token-2022, picks the kind of mint the launch creates
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
 }
 
-pub fn create_launch(
-    ctx: Context<CreateLaunch>,
-    name: String,
-    symbol: String,
-    uri: String,
-    supply: u64,
-    total_sell_amount: u64,
-    total_fund_raising: u64,
-    curve_type: CurveType,
-    migrate_type: MigrateType,
-    cliff_period: i64,
-    unlock_period: i64,
+pub fn create_launch<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreateLaunch<'info>>,
+    params: CreateLaunchParams,
 ) -> Result<()> {
-    // Validate inputs
-    require!(name.len() <= Launch::MAX_NAME_LEN, LaunchpadError::NameTooLong);
-    require!(symbol.len() <= Launch::MAX_SYMBOL_LEN, LaunchpadError::SymbolTooLong);
-    require!(uri.len() <= Launch::MAX_URI_LEN, LaunchpadError::URITooLong);
-    require!(supply > 0, LaunchpadError::InvalidSupply);
+    let CreateLaunchParams {
+        name,
+        symbol,
+        uri,
+        decimals,
+        supply,
+        total_sell_amount,
+        total_fund_raising,
+        curve_type,
+        curve_points,
+        migrate_type,
+        cliff_period,
+        unlock_period,
+        start_time,
+        deadline,
+        allocations,
+    } = params;
 
-    // Validate sell amount is between 51% and 80% of supply
-    let min_sell = supply * 51 / 100;
-    let max_sell = supply * 80 / 100;
+    // Validate inputs against the deployment's limits
+    let limits = &ctx.accounts.config.limits;
+    require!(name.len() <= limits.max_name_len as usize, LaunchpadError::NameTooLong);
+    require!(symbol.len() <= limits.max_symbol_len as usize, LaunchpadError::SymbolTooLong);
+    require!(uri.len() <= limits.max_uri_len as usize, LaunchpadError::URITooLong);
     require!(
-        total_sell_amount >= min_sell && total_sell_amount <= max_sell,
-        LaunchpadError::InvalidSellAmount
+        decimals >= limits.min_decimals && decimals <= limits.max_decimals,
+        LaunchpadError::InvalidDecimals
     );
+    require!(supply > 0, LaunchpadError::InvalidSupply);
+
+    // Validate sell amount is within the allowed share of supply
+    limits.check_sell_amount(supply, total_sell_amount)?;
 
-    // Minimum 30 SOL target
     require!(
-        total_fund_raising >= 30_000_000_000, // 30 SOL in lamports
+        total_fund_raising >= limits.min_fund_raising && total_fund_raising <= limits.max_fund_raising,
         LaunchpadError::InvalidFundRaisingTarget
     );
 
+    require!(cliff_period >= 0 && unlock_period >= 0, LaunchpadError::InvalidVestingSchedule);
+
+    // Trading opens at start_time when scheduled ahead, otherwise right away
+    let now = Clock::get()?.unix_timestamp;
+    let launch_time = start_time.unwrap_or(now);
+    require!(launch_time >= now, LaunchpadError::InvalidStartTime);
+    if let Some(deadline) = deadline {
+        require!(deadline > launch_time, LaunchpadError::InvalidDeadline);
+    }
+
+    // Constant product curves price off virtual reserves sized so the curve ends at the
+    // migration pool price
+    let (virtual_sol_reserves, virtual_token_reserves) = if curve_type == CurveType::ConstantProduct {
+        Launch::constant_product_reserves(supply, total_sell_amount, total_fund_raising)?
+    } else {
+        (0, 0)
+    };
+
+    // Create the mint and the token accounts the supply is minted into, with a vault per
+    // allocation bucket passed in index order as remaining accounts
+    ctx.accounts.create_mint(&name, &symbol, &uri, decimals, ctx.bumps.launch)?;
+    ctx.accounts.create_token_accounts(ctx.bumps.vesting_vault)?;
+    require!(ctx.remaining_accounts.len() == allocations.len(), LaunchpadError::InvalidAllocations);
+    ctx.accounts.create_allocation_vaults(ctx.remaining_accounts, ctx.program_id)?;
+
     let launch = &mut ctx.accounts.launch;
     launch.creator = ctx.accounts.creator.key();
     launch.mint = ctx.accounts.mint.key();
@@ -87,38 +168,327 @@ pub fn create_launch(
     launch.total_fund_raising = total_fund_raising;
     launch.tokens_sold = 0;
     launch.sol_raised = 0;
+    launch.sol_in_curve = 0;
     launch.curve_type = curve_type;
     launch.migrate_type = migrate_type;
+    launch.virtual_sol_reserves = virtual_sol_reserves;
+    launch.virtual_token_reserves = virtual_token_reserves;
     launch.status = LaunchStatus::Active;
     launch.creator_fee_earned = 0;
     launch.cliff_period = cliff_period;
     launch.unlock_period = unlock_period;
-    launch.launch_time = Clock::get()?.unix_timestamp;
+    launch.launch_time = launch_time;
+    launch.deadline = deadline.unwrap_or(0);
     launch.migrate_time = 0;
     launch.pool_address = Pubkey::default();
     launch.bump = ctx.bumps.launch;
 
-    // Mint total supply to launch account
-    let cpi_accounts = MintTo {
-        mint: ctx.accounts.mint.to_account_info(),
-        to: ctx.accounts.launch_token_account.to_account_info(),
-        authority: launch.to_account_info(),
+    // Piecewise curves keep their breakpoints in a companion account
+    if launch.curve_type == CurveType::Piecewise {
+        launch.validate_curve_points(&curve_points)?;
+
+        let piecewise_curve = ctx.accounts.piecewise_curve
+            .as_mut()
+            .ok_or(LaunchpadError::MissingCurvePoints)?;
+        piecewise_curve.launch = launch.key();
+        piecewise_curve.points = curve_points;
+        piecewise_curve.bump = ctx.bumps.piecewise_curve;
+    } else {
+        require!(
+            curve_points.is_empty() && ctx.accounts.piecewise_curve.is_none(),
+            LaunchpadError::InvalidCurvePoints
+        );
+    }
+
+    // Supply beyond the curve and the migration pool goes to the allocation buckets,
+    // whatever is left vests to the creator. Terms that can't fund the pool are rejected
+    let curve_points = ctx.accounts.piecewise_curve
+        .as_ref()
+        .map_or(&[][..], |curve| curve.points.as_slice());
+    let migration_tokens = launch.migration_allocation(curve_points)?;
+    let vesting_supply = supply
+        .checked_sub(total_sell_amount)
+        .and_then(|remaining| remaining.checked_sub(migration_tokens))
+        .ok_or(LaunchpadError::InvalidSellAmount)?;
+
+    let allocated = if allocations.is_empty() {
+        require!(ctx.accounts.allocation_table.is_none(), LaunchpadError::InvalidAllocations);
+        0
+    } else {
+        let allocated = AllocationTable::validate_allocations(&allocations, vesting_supply)?;
+
+        let allocation_table = ctx.accounts.allocation_table
+            .as_mut()
+            .ok_or(LaunchpadError::InvalidAllocations)?;
+        allocation_table.launch = launch.key();
+        allocation_table.allocations = allocations.into_iter().map(Allocation::from).collect();
+        allocation_table.bump = ctx.bumps.allocation_table;
+        allocated
     };
 
+    launch.vesting_amount = vesting_supply - allocated;
+    launch.vesting_claimed = 0;
+    launch.pending_creator = Pubkey::default();
+    launch.has_traded = false;
+
+    // Mint the curve and migration supply to the launch account, the creator's share to the
+    // vesting vault and each allocation to its own vault
+    let mint_key = ctx.accounts.mint.key();
     let seeds = &[
         b"launch",
-        ctx.accounts.mint.key().as_ref(),
+        mint_key.as_ref(),
         &[launch.bump],
     ];
     let signer = &[&seeds[..]];
 
+    let cpi_accounts = MintTo {
+        mint: ctx.accounts.mint.to_account_info(),
+        to: ctx.accounts.launch_token_account.to_account_info(),
+        authority: launch.to_account_info(),
+    };
     let cpi_program = ctx.accounts.token_program.to_account_info();
     let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
 
-    token::mint_to(cpi_ctx, supply)?;
+    token_interface::mint_to(cpi_ctx, supply - vesting_supply)?;
+
+    if launch.vesting_amount > 0 {
+        let cpi_accounts = MintTo {
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.vesting_vault.to_account_info(),
+            authority: launch.to_account_info(),
+        };
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+
+        token_interface::mint_to(cpi_ctx, launch.vesting_amount)?;
+    }
+
+    if let Some(allocation_table) = &ctx.accounts.allocation_table {
+        for (vault, allocation) in ctx.remaining_accounts.iter().zip(&allocation_table.allocations) {
+            let cpi_accounts = MintTo {
+                mint: ctx.accounts.mint.to_account_info(),
+                to: vault.clone(),
+                authority: launch.to_account_info(),
+            };
+            let cpi_program = ctx.accounts.token_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer);
+
+            token_interface::mint_to(cpi_ctx, allocation.amount)?;
+        }
+    }
+
+    // SPL Token mints get Metaplex metadata, updatable by the launch until migration
+    if ctx.accounts.token_program.key() != token_2022::ID {
+        let (Some(metadata_account), Some(token_metadata_program)) =
+            (&ctx.accounts.metadata, &ctx.accounts.token_metadata_program)
+        else {
+            return err!(LaunchpadError::InvalidMetadataAccounts);
+        };
+
+        metadata::create_metadata_accounts_v3(
+            CpiContext::new_with_signer(
+                token_metadata_program.to_account_info(),
+                CreateMetadataAccountsV3 {
+                    metadata: metadata_account.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    mint_authority: launch.to_account_info(),
+                    payer: ctx.accounts.creator.to_account_info(),
+                    update_authority: launch.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    rent: ctx.accounts.rent.to_account_info(),
+                },
+                signer,
+            ),
+            DataV2 {
+                name: launch.name.clone(),
+                symbol: launch.symbol.clone(),
+                uri: launch.uri.clone(),
+                seller_fee_basis_points: 0,
+                creators: None,
+                collection: None,
+                uses: None,
+            },
+            true, // is_mutable, locked at migration
+            true, // update_authority_is_signer
+            None,
+        )?;
+    } else {
+        require!(ctx.accounts.metadata.is_none(), LaunchpadError::InvalidMetadataAccounts);
+    }
+
+    // The whole supply is minted, give up the mint authority for good. The mint was created
+    // without a freeze authority, so holders can't be frozen either
+    token_interface::set_authority(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            SetAuthority {
+                current_authority: launch.to_account_info(),
+                account_or_mint: ctx.accounts.mint.to_account_info(),
+            },
+            signer,
+        ),
+        AuthorityType::MintTokens,
+        None,
+    )?;
+    launch.supply_finalized = true;
 
     msg!("Launch created: {} ({})", launch.name, launch.symbol);
     msg!("Supply: {}, Sell Amount: {}, Target: {} SOL", supply, total_sell_amount, total_fund_raising / 1_000_000_000);
 
     Ok(())
 }
+
+impl<'info> CreateLaunch<'info> {
+    /// Create the mint with the launch as mint authority and no freeze authority. Token-2022
+    /// mints point their metadata at themselves and carry name, symbol and uri on the mint
+    fn create_mint(
+        &self,
+        name: &str,
+        symbol: &str,
+        uri: &str,
+        decimals: u8,
+        launch_bump: u8,
+    ) -> Result<()> {
+        let token_program = self.token_program.key();
+        let mint = self.mint.key();
+        let launch = self.launch.key();
+        let is_token_2022 = token_program == token_2022::ID;
+
+        let token_metadata = TokenMetadata {
+            update_authority: Some(launch).try_into()?,
+            mint,
+            name: name.to_string(),
+            symbol: symbol.to_string(),
+            uri: uri.to_string(),
+            additional_metadata: vec![],
+        };
+
+        // Token-2022 grows the mint for the metadata itself, the lamports for it are paid up front
+        let (space, metadata_space) = if is_token_2022 {
+            (
+                ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[
+                    ExtensionType::MetadataPointer,
+                ])?,
+                token_metadata.tlv_size_of()?,
+            )
+        } else {
+            (Mint::LEN, 0)
+        };
+
+        system_program::create_account(
+            CpiContext::new(
+                self.system_program.to_account_info(),
+                CreateAccount {
+                    from: self.creator.to_account_info(),
+                    to: self.mint.to_account_info(),
+                },
+            ),
+            self.rent.minimum_balance(space + metadata_space),
+            space as u64,
+            &token_program,
+        )?;
+
+        if is_token_2022 {
+            invoke(
+                &metadata_pointer::instruction::initialize(&token_program, &mint, Some(launch), Some(mint))?,
+                &[self.mint.to_account_info()],
+            )?;
+        }
+
+        token_interface::initialize_mint2(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                InitializeMint2 {
+                    mint: self.mint.to_account_info(),
+                },
+            ),
+            decimals,
+            &launch,
+            None,
+        )?;
+
+        if is_token_2022 {
+            let launch_seeds = &[b"launch", mint.as_ref(), &[launch_bump]];
+            invoke_signed(
+                &spl_token_metadata_interface::instruction::initialize(
+                    &token_program,
+                    &mint,
+                    &launch,
+                    &mint,
+                    &launch,
+                    token_metadata.name,
+                    token_metadata.symbol,
+                    token_metadata.uri,
+                ),
+                &[
+                    self.mint.to_account_info(),
+                    self.launch.to_account_info(),
+                ],
+                &[&launch_seeds[..]],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Create the launch's associated token account and the vesting vault for the new mint
+    fn create_token_accounts(&self, vesting_vault_bump: u8) -> Result<()> {
+        associated_token::create(CpiContext::new(
+            self.associated_token_program.to_account_info(),
+            Create {
+                payer: self.creator.to_account_info(),
+                associated_token: self.launch_token_account.to_account_info(),
+                authority: self.launch.to_account_info(),
+                mint: self.mint.to_account_info(),
+                system_program: self.system_program.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+            },
+        ))?;
+
+        let launch_key = self.launch.key();
+        self.create_vault(
+            &self.vesting_vault.to_account_info(),
+            &[b"vesting", launch_key.as_ref(), &[vesting_vault_bump]],
+        )
+    }
+
+    /// Create the vault of each allocation bucket at `[b"vesting", launch, index]`
+    fn create_allocation_vaults(&self, vaults: &[AccountInfo<'info>], program_id: &Pubkey) -> Result<()> {
+        let launch_key = self.launch.key();
+        for (index, vault) in vaults.iter().enumerate() {
+            let index = [index as u8];
+            let (address, bump) = Pubkey::find_program_address(&[b"vesting", launch_key.as_ref(), &index], program_id);
+            require_keys_eq!(vault.key(), address, LaunchpadError::InvalidAllocations);
+
+            self.create_vault(vault, &[b"vesting", launch_key.as_ref(), &index, &[bump]])?;
+        }
+
+        Ok(())
+    }
+
+    /// Create a launch-owned token account for the mint at the PDA `vault_seeds` sign for
+    fn create_vault(&self, vault: &AccountInfo<'info>, vault_seeds: &[&[u8]]) -> Result<()> {
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                self.system_program.to_account_info(),
+                CreateAccount {
+                    from: self.creator.to_account_info(),
+                    to: vault.clone(),
+                },
+                &[vault_seeds],
+            ),
+            self.rent.minimum_balance(TokenAccount::LEN),
+            TokenAccount::LEN as u64,
+            &self.token_program.key(),
+        )?;
+
+        token_interface::initialize_account3(CpiContext::new(
+            self.token_program.to_account_info(),
+            InitializeAccount3 {
+                account: vault.clone(),
+                mint: self.mint.to_account_info(),
+                authority: self.launch.to_account_info(),
+            },
+        ))
+    }
+}
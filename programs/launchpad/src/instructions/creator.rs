@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+use crate::events::*;
+
+#[derive(Accounts)]
+pub struct ProposeCreator<'info> {
+    #[account(
+        mut,
+        seeds = [b"launch", launch.mint.as_ref()],
+        bump = launch.bump,
+        has_one = creator @ LaunchpadError::InvalidAuthority
+    )]
+    pub launch: Account<'info, Launch>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptCreator<'info> {
+    #[account(
+        mut,
+        seeds = [b"launch", launch.mint.as_ref()],
+        bump = launch.bump,
+        constraint = launch.pending_creator == new_creator.key() @ LaunchpadError::InvalidAuthority
+    )]
+    pub launch: Account<'info, Launch>,
+
+    pub new_creator: Signer<'info>,
+}
+
+pub fn propose_creator(ctx: Context<ProposeCreator>, new_creator: Pubkey) -> Result<()> {
+    let launch = &mut ctx.accounts.launch;
+    launch.pending_creator = new_creator;
+
+    emit!(CreatorProposed {
+        launch: launch.key(),
+        creator: launch.creator,
+        pending_creator: new_creator,
+    });
+    msg!("Creator transfer proposed to {}", new_creator);
+
+    Ok(())
+}
+
+/// Hand over creator fees, vesting and launch settings to the proposed creator
+pub fn accept_creator(ctx: Context<AcceptCreator>) -> Result<()> {
+    let launch = &mut ctx.accounts.launch;
+    let new_creator = ctx.accounts.new_creator.key();
+
+    emit!(CreatorTransferred {
+        launch: launch.key(),
+        old_creator: launch.creator,
+        new_creator,
+    });
+    launch.creator = new_creator;
+    launch.pending_creator = Pubkey::default();
+    msg!("Creator transferred to {}", new_creator);
+
+    Ok(())
+}
@@ -22,14 +22,17 @@ pub struct Initialize<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn initialize(ctx: Context<Initialize>, fee_percentage: u16) -> Result<()> {
-    require!(fee_percentage <= 1000, LaunchpadError::InvalidFeePercentage); // Max 10%
+pub fn initialize(ctx: Context<Initialize>, fee_percentage: u16, referral_fee: u16) -> Result<()> {
+    require!(fee_percentage <= LaunchpadConfig::MAX_FEE_BPS, LaunchpadError::InvalidFeePercentage);
+    require!(referral_fee <= LaunchpadConfig::MAX_FEE_BPS, LaunchpadError::InvalidFeePercentage);
 
     let config = &mut ctx.accounts.config;
     config.authority = ctx.accounts.authority.key();
     config.fee_percentage = fee_percentage;
     config.community_pool = ctx.accounts.community_pool.key();
-    config.referral_fee = 10; // 0.1%
+    config.referral_fee = referral_fee;
+    config.limits = LaunchLimits::DEFAULT; // Adjusted with update_launch_limits
+    config.pending_authority = Pubkey::default();
     config.bump = ctx.bumps.config;
 
     msg!("Launchpad initialized with {}% fee", fee_percentage as f64 / 100.0);
@@ -1,5 +1,9 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_spl::token_2022;
+use anchor_spl::token_interface::{self, Burn, Mint, TokenAccount, TokenInterface, TransferChecked};
+use anchor_spl::metadata::{self, Metadata, UpdateMetadataAccountsV2};
+use spl_pod::optional_keys::OptionalNonZeroPubkey;
 use crate::state::*;
 use crate::errors::*;
 
@@ -9,62 +13,104 @@ pub struct MigrateToPool<'info> {
         mut,
         seeds = [b"launch", launch.mint.as_ref()],
         bump = launch.bump,
-        constraint = launch.status == LaunchStatus::Active @ LaunchpadError::LaunchNotActive
     )]
     pub launch: Account<'info, Launch>,
 
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = authority @ LaunchpadError::InvalidAuthority
+    )]
+    pub config: Account<'info, LaunchpadConfig>,
+
+    /// Token-2022 mints hold their own metadata, which is locked here
+    #[account(mut, address = launch.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
     #[account(
         mut,
-        associated_token::mint = launch.mint,
+        associated_token::mint = mint,
         associated_token::authority = launch,
+        associated_token::token_program = token_program,
     )]
-    pub launch_token_account: Account<'info, TokenAccount>,
+    pub launch_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Breakpoints for Piecewise launches
+    #[account(has_one = launch)]
+    pub piecewise_curve: Option<Account<'info, PiecewiseCurve>>,
 
     /// CHECK: This will be the AMM pool address (CPMM or CLMM)
     #[account(mut)]
     pub pool: AccountInfo<'info>,
 
-    /// CHECK: Pool token account for the launch token
-    #[account(mut)]
-    pub pool_token_account: AccountInfo<'info>,
+    /// Pool token account for the launch token, receives the pool's share of the supply
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = pool,
+        token::token_program = token_program,
+    )]
+    pub pool_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    /// CHECK: Pool SOL/WSOL account
+    /// CHECK: Pool SOL/WSOL account, receives the raised SOL
     #[account(mut)]
     pub pool_sol_account: AccountInfo<'info>,
 
+    /// Config authority
     #[account(mut)]
     pub authority: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
+    /// CHECK: Metaplex metadata of SPL Token mints, locked at migration
+    #[account(
+        mut,
+        seeds = [b"metadata", metadata::ID.as_ref(), launch.mint.as_ref()],
+        bump,
+        seeds::program = metadata::ID
+    )]
+    pub metadata: Option<UncheckedAccount<'info>>,
+
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
 pub fn migrate_to_pool(ctx: Context<MigrateToPool>) -> Result<()> {
     let launch = &mut ctx.accounts.launch;
 
-    // Check if bonding curve goal is reached
     require!(
-        launch.sol_raised >= launch.total_fund_raising,
-        LaunchpadError::GoalNotReached
+        launch.status != LaunchStatus::Migrated,
+        LaunchpadError::AlreadyMigrated
     );
 
+    // Check if bonding curve goal is reached, buy_tokens marks the launch Completed
     require!(
-        launch.status == LaunchStatus::Active,
-        LaunchpadError::AlreadyMigrated
+        launch.status == LaunchStatus::Completed,
+        LaunchpadError::GoalNotReached
     );
 
-    // Get remaining tokens and SOL
-    let remaining_tokens = ctx.accounts.launch_token_account.amount;
-    let sol_balance = launch.to_account_info().lamports();
-
-    // Reserve 5000 lamports for rent
-    let sol_to_migrate = sol_balance.saturating_sub(5000);
+    // Curve tokens left unsold don't go to the pool, it only gets the supply set aside for it
+    let launch_tokens = ctx.accounts.launch_token_account.amount;
+    let unsold = launch.total_sell_amount
+        .checked_sub(launch.tokens_sold)
+        .ok_or(LaunchpadError::ArithmeticUnderflow)?;
+    let available_tokens = launch_tokens
+        .checked_sub(unsold)
+        .ok_or(LaunchpadError::InsufficientTokens)?;
+
+    // Pair all the raised SOL with tokens at the curve's price where it stopped, short of
+    // its final price when the goal was reached before selling out
+    let curve_points = ctx.accounts.piecewise_curve
+        .as_ref()
+        .map_or(&[][..], |curve| curve.points.as_slice());
+    let (pool_tokens, pool_sol) = launch.migration_pool(available_tokens, curve_points)?;
+    let burned_tokens = launch_tokens - pool_tokens;
 
     // Calculate LP tokens to burn (90%)
     // In a real implementation, this would interact with the actual AMM program
 
     msg!("Migrating to AMM pool");
-    msg!("Tokens: {}, SOL: {}", remaining_tokens, sol_to_migrate as f64 / 1_000_000_000.0);
+    msg!("Tokens: {}, SOL: {}", pool_tokens, pool_sol as f64 / 1_000_000_000.0);
 
     // Update launch status
     launch.status = LaunchStatus::Migrated;
@@ -77,6 +123,97 @@ pub fn migrate_to_pool(ctx: Context<MigrateToPool>) -> Result<()> {
     // 3. Burn 90% of LP tokens
     // 4. Lock 10% of LP tokens in Burn & Earn
 
+    let seeds = &[
+        b"launch",
+        launch.mint.as_ref(),
+        &[launch.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    // Hand the pool its tokens and burn the rest, unsold curve tokens and any of the pool's
+    // allocation it didn't need, so the supply only counts tokens that can trade
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.launch_token_account.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.pool_token_account.to_account_info(),
+                authority: launch.to_account_info(),
+            },
+            signer,
+        ),
+        pool_tokens,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    if burned_tokens > 0 {
+        token_interface::burn(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.mint.to_account_info(),
+                    from: ctx.accounts.launch_token_account.to_account_info(),
+                    authority: launch.to_account_info(),
+                },
+                signer,
+            ),
+            burned_tokens,
+        )?;
+        msg!("Burned {} tokens", burned_tokens);
+    }
+
+    // Lock the token's metadata for good now that it trades on the pool
+
+    if ctx.accounts.token_program.key() == token_2022::ID {
+        // Dropping the update authority freezes the on-mint metadata
+        invoke_signed(
+            &spl_token_metadata_interface::instruction::update_authority(
+                &token_2022::ID,
+                &launch.mint,
+                &launch.key(),
+                OptionalNonZeroPubkey::default(),
+            ),
+            &[
+                ctx.accounts.mint.to_account_info(),
+                launch.to_account_info(),
+            ],
+            signer,
+        )?;
+    } else {
+        let (Some(metadata_account), Some(token_metadata_program)) =
+            (&ctx.accounts.metadata, &ctx.accounts.token_metadata_program)
+        else {
+            return err!(LaunchpadError::InvalidMetadataAccounts);
+        };
+
+        metadata::update_metadata_accounts_v2(
+            CpiContext::new_with_signer(
+                token_metadata_program.to_account_info(),
+                UpdateMetadataAccountsV2 {
+                    metadata: metadata_account.to_account_info(),
+                    update_authority: launch.to_account_info(),
+                },
+                signer,
+            ),
+            None,
+            None,
+            None,
+            Some(false),
+        )?;
+    }
+
+    // Move the pool's SOL out of the launch, leaving its rent and the creator's unclaimed fees
+    let required = Rent::get()?
+        .minimum_balance(launch.to_account_info().data_len())
+        .checked_add(launch.creator_fee_earned)
+        .and_then(|required| required.checked_add(pool_sol))
+        .ok_or(LaunchpadError::ArithmeticOverflow)?;
+    require!(launch.to_account_info().lamports() >= required, LaunchpadError::InsufficientSOL);
+
+    **launch.to_account_info().try_borrow_mut_lamports()? -= pool_sol;
+    **ctx.accounts.pool_sol_account.try_borrow_mut_lamports()? += pool_sol;
+
     msg!("Migration complete. Pool address: {}", launch.pool_address);
 
     Ok(())
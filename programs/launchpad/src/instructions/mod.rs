@@ -1,15 +1,29 @@
 pub mod initialize;
+pub mod config;
 pub mod create_launch;
+pub mod update_metadata;
+pub mod presale;
+pub mod anti_snipe;
 pub mod buy_tokens;
 pub mod sell_tokens;
 pub mod migrate;
 pub mod fees;
+pub mod vesting;
+pub mod cancel;
+pub mod creator;
 pub mod referral;
 
 pub use initialize::*;
+pub use config::*;
 pub use create_launch::*;
+pub use update_metadata::*;
+pub use presale::*;
+pub use anti_snipe::*;
 pub use buy_tokens::*;
 pub use sell_tokens::*;
 pub use migrate::*;
 pub use fees::*;
+pub use vesting::*;
+pub use cancel::*;
+pub use creator::*;
 pub use referral::*;
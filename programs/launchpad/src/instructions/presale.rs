@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::*;
+
+#[derive(Accounts)]
+pub struct ConfigurePresale<'info> {
+    #[account(
+        mut,
+        seeds = [b"launch", launch.mint.as_ref()],
+        bump = launch.bump,
+        has_one = creator,
+        constraint = launch.status == LaunchStatus::Active @ LaunchpadError::LaunchNotActive
+    )]
+    pub launch: Account<'info, Launch>,
+
+    pub creator: Signer<'info>,
+}
+
+/// Restrict buys to allowlisted wallets until `end_time`, set before the first trade
+pub fn configure_presale(ctx: Context<ConfigurePresale>, merkle_root: [u8; 32], end_time: i64) -> Result<()> {
+    let launch = &mut ctx.accounts.launch;
+
+    require!(!launch.has_traded, LaunchpadError::LaunchAlreadyTraded);
+    require!(merkle_root != [0; 32], LaunchpadError::InvalidPresale);
+    require!(end_time > launch.launch_time, LaunchpadError::InvalidPresale);
+    require!(end_time > Clock::get()?.unix_timestamp, LaunchpadError::InvalidPresale);
+    if launch.deadline != 0 {
+        require!(end_time < launch.deadline, LaunchpadError::InvalidPresale);
+    }
+
+    launch.presale_merkle_root = merkle_root;
+    launch.presale_end_time = end_time;
+
+    msg!("Presale open to allowlist until {}", end_time);
+
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 use crate::state::*;
 use crate::errors::*;
 
@@ -13,12 +13,20 @@ pub struct SellTokens<'info> {
     )]
     pub launch: Account<'info, Launch>,
 
+    #[account(address = launch.mint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
     #[account(
         mut,
-        associated_token::mint = launch.mint,
+        associated_token::mint = mint,
         associated_token::authority = launch,
+        associated_token::token_program = token_program,
     )]
-    pub launch_token_account: Account<'info, TokenAccount>,
+    pub launch_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Breakpoints for Piecewise launches
+    #[account(has_one = launch)]
+    pub piecewise_curve: Option<Account<'info, PiecewiseCurve>>,
 
     #[account(
         mut,
@@ -29,70 +37,116 @@ pub struct SellTokens<'info> {
 
     #[account(
         mut,
-        associated_token::mint = launch.mint,
+        associated_token::mint = mint,
         associated_token::authority = seller,
+        associated_token::token_program = token_program,
     )]
-    pub seller_token_account: Account<'info, TokenAccount>,
+    pub seller_token_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(mut)]
     pub seller: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
 pub fn sell_tokens(ctx: Context<SellTokens>, token_amount: u64, min_sol_out: u64) -> Result<()> {
-    let launch = &mut ctx.accounts.launch;
+    let launch = &ctx.accounts.launch;
+    let curve_points = ctx.accounts.piecewise_curve
+        .as_ref()
+        .map_or(&[][..], |curve| curve.points.as_slice());
 
     require!(token_amount > 0, LaunchpadError::AmountTooSmall);
 
-    // Calculate SOL to receive based on bonding curve
-    let sol_to_receive = launch.calculate_sol_for_tokens(token_amount)?;
+    // Calculate SOL to receive by integrating the bonding curve back down
+    let sol_to_receive = launch.calculate_sol_for_tokens(token_amount, curve_points)?;
+
+    require!(sol_to_receive > 0, LaunchpadError::AmountTooSmall);
 
     // Check slippage
     require!(sol_to_receive >= min_sol_out, LaunchpadError::SlippageExceeded);
 
-    // Check if launch has enough SOL
-    let launch_balance = launch.to_account_info().lamports();
-    require!(sol_to_receive <= launch_balance, LaunchpadError::InsufficientSOL);
-
-    // Transfer tokens from seller to launch
-    token::transfer(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.seller_token_account.to_account_info(),
-                to: ctx.accounts.launch_token_account.to_account_info(),
-                authority: ctx.accounts.seller.to_account_info(),
-            },
-        ),
-        token_amount,
-    )?;
-
-    // Transfer SOL from launch to seller
-    **launch.to_account_info().try_borrow_mut_lamports()? -= sol_to_receive;
-    **ctx.accounts.seller.to_account_info().try_borrow_mut_lamports()? += sol_to_receive;
-
-    // Update launch state
-    launch.tokens_sold = launch.tokens_sold
-        .checked_sub(token_amount)
-        .ok_or(LaunchpadError::ArithmeticUnderflow)?;
-
-    launch.sol_raised = launch.sol_raised
-        .checked_sub(sol_to_receive)
-        .ok_or(LaunchpadError::ArithmeticUnderflow)?;
-
-    // Update user position
-    let position = &mut ctx.accounts.user_position;
-    position.tokens_sold = position.tokens_sold
-        .checked_add(token_amount)
-        .ok_or(LaunchpadError::ArithmeticOverflow)?;
-
-    position.sol_received = position.sol_received
-        .checked_add(sol_to_receive)
-        .ok_or(LaunchpadError::ArithmeticOverflow)?;
-
-    msg!("Sold {} tokens for {} SOL", token_amount, sol_to_receive as f64 / 1_000_000_000.0);
-
-    Ok(())
+    ctx.accounts.settle_sell(token_amount, sol_to_receive)
+}
+
+pub fn sell_tokens_exact_out(ctx: Context<SellTokens>, sol_amount: u64, max_tokens_in: u64) -> Result<()> {
+    let launch = &ctx.accounts.launch;
+    let curve_points = ctx.accounts.piecewise_curve
+        .as_ref()
+        .map_or(&[][..], |curve| curve.points.as_slice());
+
+    require!(sol_amount > 0, LaunchpadError::AmountTooSmall);
+
+    // Calculate the fewest tokens whose curve value covers sol_amount; fails with
+    // InsufficientSOL if that is more than the curve holds
+    let token_amount = launch.calculate_tokens_for_sol_out(sol_amount, curve_points)?;
+
+    require!(token_amount > 0, LaunchpadError::AmountTooSmall);
+
+    // Check slippage
+    require!(token_amount <= max_tokens_in, LaunchpadError::SlippageExceeded);
+
+    // Pay out exactly sol_amount, rounding dust stays with the curve
+    ctx.accounts.settle_sell(token_amount, sol_amount)
+}
+
+impl<'info> SellTokens<'info> {
+    /// Take `token_amount` back from the seller, pay out `sol_to_receive` from the curve
+    /// reserve and record the trade on the launch and the seller's position
+    fn settle_sell(&mut self, token_amount: u64, sol_to_receive: u64) -> Result<()> {
+        let launch = &mut self.launch;
+        launch.check_trading_open(Clock::get()?.unix_timestamp)?;
+
+        // Sells are paid from the curve reserve only, never from creator fees or rent
+        require!(sol_to_receive <= launch.sol_raised, LaunchpadError::InsufficientSOL);
+
+        // Transfer tokens from seller to launch
+        token_interface::transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.seller_token_account.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    to: self.launch_token_account.to_account_info(),
+                    authority: self.seller.to_account_info(),
+                },
+            ),
+            token_amount,
+            self.mint.decimals,
+        )?;
+
+        // Transfer SOL from launch to seller
+        **launch.to_account_info().try_borrow_mut_lamports()? -= sol_to_receive;
+        **self.seller.to_account_info().try_borrow_mut_lamports()? += sol_to_receive;
+
+        // Update launch state
+        launch.tokens_sold = launch.tokens_sold
+            .checked_sub(token_amount)
+            .ok_or(LaunchpadError::ArithmeticUnderflow)?;
+
+        launch.sol_raised = launch.sol_raised
+            .checked_sub(sol_to_receive)
+            .ok_or(LaunchpadError::ArithmeticUnderflow)?;
+
+        // Update user position
+        let position = &mut self.user_position;
+        position.tokens_sold = position.tokens_sold
+            .checked_add(token_amount)
+            .ok_or(LaunchpadError::ArithmeticOverflow)?;
+
+        position.sol_received = position.sol_received
+            .checked_add(sol_to_receive)
+            .ok_or(LaunchpadError::ArithmeticOverflow)?;
+
+        // Selling tokens received from elsewhere can take out more than was put in
+        let sol_in_curve = position.sol_in_curve.saturating_sub(sol_to_receive);
+        launch.sol_in_curve = launch.sol_in_curve
+            .checked_sub(position.sol_in_curve - sol_in_curve)
+            .ok_or(LaunchpadError::ArithmeticUnderflow)?;
+        position.sol_in_curve = sol_in_curve;
+
+        msg!("Sold {} tokens for {} SOL", token_amount, sol_to_receive as f64 / 1_000_000_000.0);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,198 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::system_program;
+use anchor_spl::token_2022::{self, spl_token_2022};
+use anchor_spl::token_2022::spl_token_2022::extension::{BaseStateWithExtensions, StateWithExtensions};
+use anchor_spl::token_interface::{Mint, TokenInterface};
+use anchor_spl::metadata::{self, Metadata, UpdateMetadataAccountsV2};
+use anchor_spl::metadata::mpl_token_metadata::types::DataV2;
+use spl_token_metadata_interface::state::{Field, TokenMetadata};
+use crate::state::*;
+use crate::errors::*;
+use crate::events::*;
+
+#[derive(Accounts)]
+pub struct UpdateLaunchMetadata<'info> {
+    #[account(
+        mut,
+        seeds = [b"launch", launch.mint.as_ref()],
+        bump = launch.bump,
+        has_one = creator,
+        has_one = mint,
+        constraint = launch.status != LaunchStatus::Migrated @ LaunchpadError::MetadataLocked
+    )]
+    pub launch: Account<'info, Launch>,
+
+    /// Token-2022 mints hold their own metadata
+    #[account(mut, mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, LaunchpadConfig>,
+
+    /// Pays for the mint to grow when Token-2022 metadata gets longer
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    /// CHECK: Metaplex metadata of SPL Token mints, omitted for Token-2022 mints
+    #[account(
+        mut,
+        seeds = [b"metadata", metadata::ID.as_ref(), launch.mint.as_ref()],
+        bump,
+        seeds::program = metadata::ID
+    )]
+    pub metadata: Option<UncheckedAccount<'info>>,
+
+    pub token_metadata_program: Option<Program<'info, Metadata>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Change the launch's uri at any time before migration, and its name and symbol until the
+/// first trade. The token's metadata is updated to match
+pub fn update_launch_metadata(
+    ctx: Context<UpdateLaunchMetadata>,
+    name: Option<String>,
+    symbol: Option<String>,
+    uri: Option<String>,
+) -> Result<()> {
+    let limits = &ctx.accounts.config.limits;
+    let launch = &mut ctx.accounts.launch;
+
+    // Buyers traded on the name and symbol, only the uri can move after that
+    if name.is_some() || symbol.is_some() {
+        require!(!launch.has_traded, LaunchpadError::LaunchAlreadyTraded);
+    }
+
+    if let Some(name) = name {
+        require!(name.len() <= limits.max_name_len as usize, LaunchpadError::NameTooLong);
+        launch.name = name;
+    }
+
+    if let Some(symbol) = symbol {
+        require!(symbol.len() <= limits.max_symbol_len as usize, LaunchpadError::SymbolTooLong);
+        launch.symbol = symbol;
+    }
+
+    if let Some(uri) = uri {
+        require!(uri.len() <= limits.max_uri_len as usize, LaunchpadError::URITooLong);
+        launch.uri = uri;
+    }
+
+    let seeds = &[
+        b"launch",
+        launch.mint.as_ref(),
+        &[launch.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let mint_info = ctx.accounts.mint.to_account_info();
+
+    if ctx.accounts.token_program.key() == token_2022::ID {
+        // Work out which on-mint fields change and how far the mint grows
+        let (changes, current_len, new_len) = {
+            let data = mint_info.try_borrow_data()?;
+            let mint_state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+            let token_metadata = mint_state.get_variable_len_extension::<TokenMetadata>()?;
+
+            let mut changes = vec![];
+            if token_metadata.name != launch.name {
+                changes.push((Field::Name, launch.name.clone()));
+            }
+            if token_metadata.symbol != launch.symbol {
+                changes.push((Field::Symbol, launch.symbol.clone()));
+            }
+            if token_metadata.uri != launch.uri {
+                changes.push((Field::Uri, launch.uri.clone()));
+            }
+
+            let updated = TokenMetadata {
+                name: launch.name.clone(),
+                symbol: launch.symbol.clone(),
+                uri: launch.uri.clone(),
+                ..token_metadata.clone()
+            };
+            let new_len = (data.len() + updated.tlv_size_of()?)
+                .checked_sub(token_metadata.tlv_size_of()?)
+                .ok_or(LaunchpadError::ArithmeticUnderflow)?;
+
+            (changes, data.len(), new_len)
+        };
+
+        // Top up rent first, Token-2022 resizes the mint for the new values itself
+        let required_lamports = Rent::get()?.minimum_balance(new_len.max(current_len));
+        let top_up = required_lamports.saturating_sub(mint_info.lamports());
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.creator.to_account_info(),
+                        to: mint_info.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+
+        for (field, value) in changes {
+            invoke_signed(
+                &spl_token_metadata_interface::instruction::update_field(
+                    &token_2022::ID,
+                    &launch.mint,
+                    &launch.key(),
+                    field,
+                    value,
+                ),
+                &[
+                    mint_info.clone(),
+                    launch.to_account_info(),
+                ],
+                signer,
+            )?;
+        }
+    } else {
+        let (Some(metadata_account), Some(token_metadata_program)) =
+            (&ctx.accounts.metadata, &ctx.accounts.token_metadata_program)
+        else {
+            return err!(LaunchpadError::InvalidMetadataAccounts);
+        };
+
+        metadata::update_metadata_accounts_v2(
+            CpiContext::new_with_signer(
+                token_metadata_program.to_account_info(),
+                UpdateMetadataAccountsV2 {
+                    metadata: metadata_account.to_account_info(),
+                    update_authority: launch.to_account_info(),
+                },
+                signer,
+            ),
+            None,
+            Some(DataV2 {
+                name: launch.name.clone(),
+                symbol: launch.symbol.clone(),
+                uri: launch.uri.clone(),
+                seller_fee_basis_points: 0,
+                creators: None,
+                collection: None,
+                uses: None,
+            }),
+            None,
+            None,
+        )?;
+    }
+
+    emit!(LaunchMetadataUpdated {
+        launch: launch.key(),
+        name: launch.name.clone(),
+        symbol: launch.symbol.clone(),
+        uri: launch.uri.clone(),
+    });
+    msg!("Launch metadata updated: {} ({}) {}", launch.name, launch.symbol, launch.uri);
+
+    Ok(())
+}
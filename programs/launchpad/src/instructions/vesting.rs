@@ -0,0 +1,321 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Burn, Mint, TokenAccount, TokenInterface, TransferChecked};
+use anchor_spl::associated_token::AssociatedToken;
+use crate::state::*;
+use crate::errors::*;
+use crate::events::*;
+use crate::utils::vested_amount;
+
+#[derive(Accounts)]
+pub struct ClaimVestedTokens<'info> {
+    #[account(
+        mut,
+        seeds = [b"launch", launch.mint.as_ref()],
+        bump = launch.bump,
+        has_one = creator,
+        has_one = mint,
+        constraint = launch.status == LaunchStatus::Migrated @ LaunchpadError::VestingNotEnded
+    )]
+    pub launch: Account<'info, Launch>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"vesting", launch.key().as_ref()],
+        bump
+    )]
+    pub vesting_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = creator,
+        associated_token::mint = mint,
+        associated_token::authority = creator,
+        associated_token::token_program = token_program,
+    )]
+    pub creator_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u8)]
+pub struct ClaimAllocation<'info> {
+    #[account(
+        seeds = [b"launch", launch.mint.as_ref()],
+        bump = launch.bump,
+        has_one = mint,
+        constraint = launch.status == LaunchStatus::Migrated @ LaunchpadError::VestingNotEnded
+    )]
+    pub launch: Account<'info, Launch>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"allocations", launch.key().as_ref()],
+        bump = allocation_table.bump,
+        has_one = launch
+    )]
+    pub allocation_table: Account<'info, AllocationTable>,
+
+    /// Holds the bucket at index until it vests
+    #[account(
+        mut,
+        seeds = [b"vesting", launch.key().as_ref(), &[index]],
+        bump
+    )]
+    pub allocation_vault: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: Beneficiary of the allocation at index, checked against the table
+    #[account(
+        constraint = allocation_table.allocations
+            .get(index as usize)
+            .is_some_and(|allocation| allocation.beneficiary == beneficiary.key())
+            @ LaunchpadError::AllocationNotFound
+    )]
+    pub beneficiary: AccountInfo<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = mint,
+        associated_token::authority = beneficiary,
+        associated_token::token_program = token_program,
+    )]
+    pub beneficiary_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Anyone may crank a claim, paying for the beneficiary's token account if needed
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BurnVestingTokens<'info> {
+    #[account(
+        seeds = [b"launch", launch.mint.as_ref()],
+        bump = launch.bump,
+        has_one = mint,
+        constraint = launch.status == LaunchStatus::Cancelled
+            || launch.status == LaunchStatus::Failed @ LaunchpadError::LaunchNotCancelled
+    )]
+    pub launch: Account<'info, Launch>,
+
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"vesting", launch.key().as_ref()],
+        bump
+    )]
+    pub vesting_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+#[instruction(index: u8)]
+pub struct BurnAllocationTokens<'info> {
+    #[account(
+        seeds = [b"launch", launch.mint.as_ref()],
+        bump = launch.bump,
+        has_one = mint,
+        constraint = launch.status == LaunchStatus::Cancelled
+            || launch.status == LaunchStatus::Failed @ LaunchpadError::LaunchNotCancelled
+    )]
+    pub launch: Account<'info, Launch>,
+
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"vesting", launch.key().as_ref(), &[index]],
+        bump
+    )]
+    pub allocation_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn claim_vested_tokens(ctx: Context<ClaimVestedTokens>) -> Result<()> {
+    let launch = &mut ctx.accounts.launch;
+
+    // Vesting runs from migration: nothing until the cliff, then linear over unlock_period
+    let vested = vested_amount(
+        launch.vesting_amount,
+        launch.migrate_time,
+        launch.cliff_period,
+        launch.unlock_period,
+        Clock::get()?.unix_timestamp,
+    );
+    let claimable = vested
+        .checked_sub(launch.vesting_claimed)
+        .ok_or(LaunchpadError::ArithmeticUnderflow)?;
+
+    require!(claimable > 0, LaunchpadError::VestingNotEnded);
+
+    // Transfer vested tokens from vault to creator
+    let seeds = &[
+        b"launch",
+        launch.mint.as_ref(),
+        &[launch.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vesting_vault.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.creator_token_account.to_account_info(),
+                authority: launch.to_account_info(),
+            },
+            signer,
+        ),
+        claimable,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    launch.vesting_claimed = vested;
+
+    msg!("Claimed {} vested tokens, {} of {} released", claimable, vested, launch.vesting_amount);
+
+    Ok(())
+}
+
+pub fn claim_allocation(ctx: Context<ClaimAllocation>, index: u8) -> Result<()> {
+    let launch = &ctx.accounts.launch;
+    let allocation = ctx.accounts.allocation_table.allocations
+        .get_mut(index as usize)
+        .ok_or(LaunchpadError::AllocationNotFound)?;
+
+    // Same schedule shape as creator vesting, with the bucket's own cliff and unlock
+    let vested = vested_amount(
+        allocation.amount,
+        launch.migrate_time,
+        allocation.cliff_period,
+        allocation.unlock_period,
+        Clock::get()?.unix_timestamp,
+    );
+    let claimable = vested
+        .checked_sub(allocation.claimed)
+        .ok_or(LaunchpadError::ArithmeticUnderflow)?;
+
+    require!(claimable > 0, LaunchpadError::VestingNotEnded);
+
+    allocation.claimed = vested;
+
+    // Transfer vested tokens from the bucket's vault to beneficiary
+    let seeds = &[
+        b"launch",
+        launch.mint.as_ref(),
+        &[launch.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.allocation_vault.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.beneficiary_token_account.to_account_info(),
+                authority: launch.to_account_info(),
+            },
+            signer,
+        ),
+        claimable,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    msg!("Released {} tokens of allocation {} to {}", claimable, index, ctx.accounts.beneficiary.key());
+
+    Ok(())
+}
+
+/// Burn what is left in the vesting vault of a launch that will never migrate, so the
+/// creator's and beneficiaries' tokens don't sit there for good
+pub fn burn_vesting_tokens(ctx: Context<BurnVestingTokens>) -> Result<()> {
+    let launch = &ctx.accounts.launch;
+    let amount = ctx.accounts.vesting_vault.amount;
+
+    require!(amount > 0, LaunchpadError::VestingVaultEmpty);
+
+    let seeds = &[
+        b"launch",
+        launch.mint.as_ref(),
+        &[launch.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    token_interface::burn(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.mint.to_account_info(),
+                from: ctx.accounts.vesting_vault.to_account_info(),
+                authority: launch.to_account_info(),
+            },
+            signer,
+        ),
+        amount,
+    )?;
+
+    emit!(VestingBurned {
+        launch: launch.key(),
+        amount,
+    });
+    msg!("Burned {} unvested tokens", amount);
+
+    Ok(())
+}
+
+/// Burn what is left in an allocation bucket's vault of a launch that will never migrate
+pub fn burn_allocation_tokens(ctx: Context<BurnAllocationTokens>, index: u8) -> Result<()> {
+    let launch = &ctx.accounts.launch;
+    let amount = ctx.accounts.allocation_vault.amount;
+
+    require!(amount > 0, LaunchpadError::VestingVaultEmpty);
+
+    let seeds = &[
+        b"launch",
+        launch.mint.as_ref(),
+        &[launch.bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    token_interface::burn(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.mint.to_account_info(),
+                from: ctx.accounts.allocation_vault.to_account_info(),
+                authority: launch.to_account_info(),
+            },
+            signer,
+        ),
+        amount,
+    )?;
+
+    emit!(AllocationBurned {
+        launch: launch.key(),
+        index,
+        amount,
+    });
+    msg!("Burned {} unvested tokens of allocation {}", amount, index);
+
+    Ok(())
+}
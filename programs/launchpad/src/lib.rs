@@ -1,6 +1,4 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
-use anchor_spl::associated_token::AssociatedToken;
 
 declare_id!("DRay6fNdQ5J82H7xV6uq2aV3mNrUZ1J4PgSKsWgptcm6");
 
@@ -8,52 +6,102 @@ pub mod state;
 pub mod instructions;
 pub mod errors;
 pub mod utils;
+pub mod fixed_point;
+pub mod events;
 
 use state::*;
 use instructions::*;
-use errors::*;
 
 #[program]
 pub mod launchpad {
     use super::*;
 
     /// Initialize the launchpad program
-    pub fn initialize(ctx: Context<Initialize>, fee_percentage: u16) -> Result<()> {
-        instructions::initialize(ctx, fee_percentage)
+    pub fn initialize(ctx: Context<Initialize>, fee_percentage: u16, referral_fee: u16) -> Result<()> {
+        instructions::initialize(ctx, fee_percentage, referral_fee)
+    }
+
+    /// Update fee settings and the community pool, leaving None fields unchanged
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        fee_percentage: Option<u16>,
+        community_pool: Option<Pubkey>,
+        referral_fee: Option<u16>,
+    ) -> Result<()> {
+        instructions::update_config(ctx, fee_percentage, community_pool, referral_fee)
+    }
+
+    /// Update the bounds enforced on new launches
+    pub fn update_launch_limits(ctx: Context<UpdateConfig>, limits: LaunchLimits) -> Result<()> {
+        instructions::update_launch_limits(ctx, limits)
+    }
+
+    /// Propose a new config authority
+    pub fn propose_authority(ctx: Context<UpdateConfig>, new_authority: Pubkey) -> Result<()> {
+        instructions::propose_authority(ctx, new_authority)
+    }
+
+    /// Accept a proposed config authority transfer
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        instructions::accept_authority(ctx)
     }
 
     /// Create a new token launch with bonding curve
-    pub fn create_launch(
-        ctx: Context<CreateLaunch>,
-        name: String,
-        symbol: String,
-        uri: String,
-        supply: u64,
-        total_sell_amount: u64,
-        total_fund_raising: u64,
-        curve_type: CurveType,
-        migrate_type: MigrateType,
-        cliff_period: i64,
-        unlock_period: i64,
+    pub fn create_launch<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateLaunch<'info>>,
+        params: CreateLaunchParams,
+    ) -> Result<()> {
+        instructions::create_launch(ctx, params)
+    }
+
+    /// Change a launch's uri before migration, and its name and symbol before the first trade
+    pub fn update_launch_metadata(
+        ctx: Context<UpdateLaunchMetadata>,
+        name: Option<String>,
+        symbol: Option<String>,
+        uri: Option<String>,
+    ) -> Result<()> {
+        instructions::update_launch_metadata(ctx, name, symbol, uri)
+    }
+
+    /// Open an allowlisted presale phase before public trading
+    pub fn configure_presale(ctx: Context<ConfigurePresale>, merkle_root: [u8; 32], end_time: i64) -> Result<()> {
+        instructions::configure_presale(ctx, merkle_root, end_time)
+    }
+
+    /// Cap tokens bought per transaction and held per wallet, optionally for a window after launch
+    pub fn configure_purchase_caps(
+        ctx: Context<ConfigureAntiSnipe>,
+        max_tokens_per_tx: u64,
+        max_tokens_per_wallet: u64,
+        duration: i64,
     ) -> Result<()> {
-        instructions::create_launch(
-            ctx,
-            name,
-            symbol,
-            uri,
-            supply,
-            total_sell_amount,
-            total_fund_raising,
-            curve_type,
-            migrate_type,
-            cliff_period,
-            unlock_period,
-        )
+        instructions::configure_purchase_caps(ctx, max_tokens_per_tx, max_tokens_per_wallet, duration)
+    }
+
+    /// Charge a decaying anti-sniper fee on buys for a window after launch
+    pub fn configure_launch_fee(ctx: Context<ConfigureAntiSnipe>, launch_fee_bps: u16, duration: i64) -> Result<()> {
+        instructions::configure_launch_fee(ctx, launch_fee_bps, duration)
     }
 
     /// Buy tokens from bonding curve
-    pub fn buy_tokens(ctx: Context<BuyTokens>, sol_amount: u64, min_tokens_out: u64) -> Result<()> {
-        instructions::buy_tokens(ctx, sol_amount, min_tokens_out)
+    pub fn buy_tokens(
+        ctx: Context<BuyTokens>,
+        sol_amount: u64,
+        min_tokens_out: u64,
+        presale_proof: Option<PresaleProof>,
+    ) -> Result<()> {
+        instructions::buy_tokens(ctx, sol_amount, min_tokens_out, presale_proof)
+    }
+
+    /// Buy an exact amount of tokens from bonding curve, paying at most max_sol_in
+    pub fn buy_tokens_exact_out(
+        ctx: Context<BuyTokens>,
+        token_amount: u64,
+        max_sol_in: u64,
+        presale_proof: Option<PresaleProof>,
+    ) -> Result<()> {
+        instructions::buy_tokens_exact_out(ctx, token_amount, max_sol_in, presale_proof)
     }
 
     /// Sell tokens to bonding curve
@@ -61,6 +109,11 @@ pub mod launchpad {
         instructions::sell_tokens(ctx, token_amount, min_sol_out)
     }
 
+    /// Sell just enough tokens to bonding curve to receive sol_amount, selling at most max_tokens_in
+    pub fn sell_tokens_exact_out(ctx: Context<SellTokens>, sol_amount: u64, max_tokens_in: u64) -> Result<()> {
+        instructions::sell_tokens_exact_out(ctx, sol_amount, max_tokens_in)
+    }
+
     /// Migrate liquidity to AMM pool when bonding curve goal is reached
     pub fn migrate_to_pool(ctx: Context<MigrateToPool>) -> Result<()> {
         instructions::migrate_to_pool(ctx)
@@ -71,6 +124,51 @@ pub mod launchpad {
         instructions::claim_creator_fees(ctx)
     }
 
+    /// Claim creator tokens vested since migration
+    pub fn claim_vested_tokens(ctx: Context<ClaimVestedTokens>) -> Result<()> {
+        instructions::claim_vested_tokens(ctx)
+    }
+
+    /// Release vested tokens of an allocation bucket to its beneficiary
+    pub fn claim_allocation(ctx: Context<ClaimAllocation>, index: u8) -> Result<()> {
+        instructions::claim_allocation(ctx, index)
+    }
+
+    /// Burn the vesting vault of a cancelled or failed launch, callable by anyone
+    pub fn burn_vesting_tokens(ctx: Context<BurnVestingTokens>) -> Result<()> {
+        instructions::burn_vesting_tokens(ctx)
+    }
+
+    /// Burn an allocation bucket's vault of a cancelled or failed launch, callable by anyone
+    pub fn burn_allocation_tokens(ctx: Context<BurnAllocationTokens>, index: u8) -> Result<()> {
+        instructions::burn_allocation_tokens(ctx, index)
+    }
+
+    /// Cancel a launch before migration so holders can claim refunds
+    pub fn cancel_launch(ctx: Context<CancelLaunch>) -> Result<()> {
+        instructions::cancel_launch(ctx)
+    }
+
+    /// Mark an Active launch past its deadline as Failed, callable by anyone
+    pub fn fail_launch(ctx: Context<FailLaunch>) -> Result<()> {
+        instructions::fail_launch(ctx)
+    }
+
+    /// Return tokens to a cancelled or failed launch for a refund from its SOL reserve
+    pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
+        instructions::claim_refund(ctx)
+    }
+
+    /// Propose a new creator for a launch
+    pub fn propose_creator(ctx: Context<ProposeCreator>, new_creator: Pubkey) -> Result<()> {
+        instructions::propose_creator(ctx, new_creator)
+    }
+
+    /// Accept a proposed launch creator transfer
+    pub fn accept_creator(ctx: Context<AcceptCreator>) -> Result<()> {
+        instructions::accept_creator(ctx)
+    }
+
     /// Add referral for fee sharing
     pub fn add_referral(ctx: Context<AddReferral>, referrer: Pubkey) -> Result<()> {
         instructions::add_referral(ctx, referrer)
@@ -1,4 +1,6 @@
 use anchor_lang::prelude::*;
+use crate::errors::*;
+use crate::fixed_point::{mul_div, Q64x64, Rounding};
 
 /// Global configuration for the launchpad
 #[account]
@@ -7,11 +9,88 @@ pub struct LaunchpadConfig {
     pub fee_percentage: u16,        // Base fee in basis points (100 = 1%)
     pub community_pool: Pubkey,     // 50% of fees go here
     pub referral_fee: u16,          // Referral fee in basis points (10 = 0.1%)
+    pub limits: LaunchLimits,       // Bounds enforced on create_launch
+    pub pending_authority: Pubkey,  // Proposed authority until it accepts, default if none
     pub bump: u8,
 }
 
 impl LaunchpadConfig {
-    pub const LEN: usize = 8 + 32 + 2 + 32 + 2 + 1;
+    pub const LEN: usize = 8 + 32 + 2 + 32 + 2 + LaunchLimits::LEN + 32 + 1;
+
+    /// Max fee_percentage and referral_fee (10%)
+    pub const MAX_FEE_BPS: u16 = 1000;
+}
+
+/// Bounds a new launch has to stay within, set per deployment by the config authority
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LaunchLimits {
+    pub min_sell_bps: u16,          // Min share of supply sold on the curve (5100 = 51%)
+    pub max_sell_bps: u16,          // Max share of supply sold on the curve
+    pub min_fund_raising: u64,      // Min SOL target in lamports
+    pub max_fund_raising: u64,      // Max SOL target in lamports
+    pub min_decimals: u8,
+    pub max_decimals: u8,
+    pub max_name_len: u8,
+    pub max_symbol_len: u8,
+    pub max_uri_len: u16,
+}
+
+impl LaunchLimits {
+    pub const LEN: usize = 2 + 2 + 8 + 8 + 1 + 1 + 1 + 1 + 2;
+
+    /// Decimals above this overflow u64 supplies of even a few tokens
+    pub const MAX_DECIMALS: u8 = 18;
+
+    /// 51%-80% sold on the curve, at least 30 SOL raised, 9 decimals
+    pub const DEFAULT: Self = Self {
+        min_sell_bps: 5100,
+        max_sell_bps: 8000,
+        min_fund_raising: 30_000_000_000,
+        max_fund_raising: u64::MAX,
+        min_decimals: 9,
+        max_decimals: 9,
+        max_name_len: Launch::MAX_NAME_LEN as u8,
+        max_symbol_len: Launch::MAX_SYMBOL_LEN as u8,
+        max_uri_len: Launch::MAX_URI_LEN as u16,
+    };
+
+    /// Check the limits are consistent and fit the space reserved in `Launch`
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.min_sell_bps > 0
+                && self.min_sell_bps <= self.max_sell_bps
+                && self.max_sell_bps < 10_000,
+            LaunchpadError::InvalidLaunchLimits
+        );
+        require!(
+            self.min_fund_raising > 0 && self.min_fund_raising <= self.max_fund_raising,
+            LaunchpadError::InvalidLaunchLimits
+        );
+        require!(
+            self.min_decimals <= self.max_decimals && self.max_decimals <= Self::MAX_DECIMALS,
+            LaunchpadError::InvalidLaunchLimits
+        );
+        require!(
+            self.max_name_len as usize <= Launch::MAX_NAME_LEN
+                && self.max_symbol_len as usize <= Launch::MAX_SYMBOL_LEN
+                && self.max_uri_len as usize <= Launch::MAX_URI_LEN,
+            LaunchpadError::InvalidLaunchLimits
+        );
+
+        Ok(())
+    }
+
+    /// Check a sell amount against the sell ratio bounds for `supply`
+    pub fn check_sell_amount(&self, supply: u64, total_sell_amount: u64) -> Result<()> {
+        let min_sell = supply as u128 * self.min_sell_bps as u128 / 10_000;
+        let max_sell = supply as u128 * self.max_sell_bps as u128 / 10_000;
+        require!(
+            total_sell_amount as u128 >= min_sell && total_sell_amount as u128 <= max_sell,
+            LaunchpadError::InvalidSellAmount
+        );
+
+        Ok(())
+    }
 }
 
 /// Individual token launch configuration
@@ -29,11 +108,27 @@ pub struct Launch {
     pub sol_raised: u64,
     pub curve_type: CurveType,
     pub migrate_type: MigrateType,
+    pub virtual_sol_reserves: u64,     // ConstantProduct only: virtual SOL reserve at launch
+    pub virtual_token_reserves: u64,   // ConstantProduct only: virtual token reserve at launch
     pub status: LaunchStatus,
     pub creator_fee_earned: u64,
     pub cliff_period: i64,             // Vesting cliff in seconds
     pub unlock_period: i64,            // Vesting unlock period in seconds
-    pub launch_time: i64,
+    pub launch_time: i64,              // Trading opens at this time, may be in the future
+    pub deadline: i64,                 // Fails with refunds if the goal isn't met by then, 0 for none
+    pub sol_in_curve: u64,             // Sum of every position's sol_in_curve
+    pub presale_merkle_root: [u8; 32], // Allowlist for the presale phase, zeroed for none
+    pub presale_end_time: i64,         // Buys need an allowlist proof until then
+    pub max_tokens_per_tx: u64,        // Largest single buy, 0 for uncapped
+    pub max_tokens_per_wallet: u64,    // Most tokens a wallet may hold from the curve, 0 for uncapped
+    pub purchase_caps_duration: i64,   // Caps apply this long after launch_time, 0 for always
+    pub launch_fee_bps: u16,           // Buy fee at launch_time, decaying to the config fee, 0 for none
+    pub launch_fee_duration: i64,      // Seconds the launch fee takes to decay
+    pub vesting_amount: u64,           // Creator tokens held in the vesting vault
+    pub vesting_claimed: u64,          // Creator tokens released from the vesting vault
+    pub supply_finalized: bool,        // Mint authority revoked and no freeze authority
+    pub pending_creator: Pubkey,       // Proposed creator until it accepts, default if none
+    pub has_traded: bool,              // Set by the first buy, even if everything is sold back
     pub migrate_time: i64,
     pub pool_address: Pubkey,
     pub bump: u8,
@@ -44,6 +139,17 @@ impl Launch {
     pub const MAX_SYMBOL_LEN: usize = 10;
     pub const MAX_URI_LEN: usize = 200;
 
+    /// Highest starting fee for the anti-sniper launch fee (90%)
+    pub const MAX_LAUNCH_FEE_BPS: u16 = 9000;
+
+    /// Longest window the launch fee may take to decay (1 hour), it's meant for the first
+    /// moments of trading, not as a lasting fee
+    pub const MAX_LAUNCH_FEE_DURATION: i64 = 3600;
+
+    /// Units either side of a closed-form inverse searched for the exact position, a margin
+    /// over the few units its roundings can be off by on the largest supplies
+    const INVERSE_SLACK: u64 = 16;
+
     pub const LEN: usize = 8 +          // discriminator
         32 +                            // creator
         32 +                            // mint
@@ -57,11 +163,27 @@ impl Launch {
         8 +                             // sol_raised
         1 +                             // curve_type
         1 +                             // migrate_type
+        8 +                             // virtual_sol_reserves
+        8 +                             // virtual_token_reserves
         1 +                             // status
         8 +                             // creator_fee_earned
         8 +                             // cliff_period
         8 +                             // unlock_period
         8 +                             // launch_time
+        8 +                             // deadline
+        8 +                             // sol_in_curve
+        32 +                            // presale_merkle_root
+        8 +                             // presale_end_time
+        8 +                             // max_tokens_per_tx
+        8 +                             // max_tokens_per_wallet
+        8 +                             // purchase_caps_duration
+        2 +                             // launch_fee_bps
+        8 +                             // launch_fee_duration
+        8 +                             // vesting_amount
+        8 +                             // vesting_claimed
+        1 +                             // supply_finalized
+        32 +                            // pending_creator
+        1 +                             // has_traded
         8 +                             // migrate_time
         32 +                            // pool_address
         1;                              // bump
@@ -71,61 +193,588 @@ impl Launch {
         if self.total_sell_amount == 0 {
             return 0;
         }
-        (self.tokens_sold * 100) / self.total_sell_amount
+        mul_div(self.tokens_sold as u128, 100, self.total_sell_amount as u128, Rounding::Down)
+            .unwrap_or(0) as u64
+    }
+
+    /// Share of the remaining SOL reserve owed for returning `token_amount` after cancellation
+    ///
+    /// Rounds down, so refunds claimed in any order never exceed the reserve and the last
+    /// holder to return the outstanding tokens receives whatever is left.
+    pub fn refund_for(&self, token_amount: u64) -> Result<u64> {
+        require!(token_amount <= self.tokens_sold, LaunchpadError::InsufficientTokens);
+        if token_amount == 0 {
+            return Ok(0);
+        }
+
+        mul_div(self.sol_raised as u128, token_amount as u128, self.tokens_sold as u128, Rounding::Down)
+            .map(|refund| refund as u64)
+            .ok_or(error!(LaunchpadError::ArithmeticOverflow))
+    }
+
+    /// Share of the remaining SOL reserve owed to a position returning tokens worth
+    /// `net_sol_refund` of its `sol_in_curve` after the launch failed
+    ///
+    /// Positions that sold at a profit took out more than they put in, so the reserve can
+    /// be short of the total net SOL outstanding. Splitting it pro rata to that total and
+    /// rounding down keeps every claim payable, in any order.
+    pub fn net_refund_for(&self, net_sol_refund: u64) -> Result<u64> {
+        require!(net_sol_refund <= self.sol_in_curve, LaunchpadError::InsufficientSOL);
+        if net_sol_refund == 0 {
+            return Ok(0);
+        }
+
+        mul_div(self.sol_raised as u128, net_sol_refund as u128, self.sol_in_curve as u128, Rounding::Down)
+            .map(|refund| refund as u64)
+            .ok_or(error!(LaunchpadError::ArithmeticOverflow))
+    }
+
+    /// Whether the deadline passed with the curve still short of its goal
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.status == LaunchStatus::Active && self.deadline != 0 && now >= self.deadline
+    }
+
+    /// Whether buys at `now` are limited to the presale allowlist
+    pub fn is_presale(&self, now: i64) -> bool {
+        self.presale_merkle_root != [0; 32] && now < self.presale_end_time
+    }
+
+    /// Whether the per-transaction and per-wallet caps apply to buys at `now`
+    pub fn purchase_caps_active(&self, now: i64) -> bool {
+        self.purchase_caps_duration == 0
+            || now < self.launch_time.saturating_add(self.purchase_caps_duration)
+    }
+
+    /// Buy fee in basis points at `now`, decaying linearly from `launch_fee_bps` at
+    /// `launch_time` to `base_fee_bps` after `launch_fee_duration`
+    pub fn fee_bps_at(&self, base_fee_bps: u16, now: i64) -> u16 {
+        let elapsed = now.saturating_sub(self.launch_time).max(0);
+        if self.launch_fee_bps <= base_fee_bps || elapsed >= self.launch_fee_duration {
+            return base_fee_bps;
+        }
+
+        let surplus = (self.launch_fee_bps - base_fee_bps) as i128;
+        let remaining = self.launch_fee_duration as i128 - elapsed as i128;
+        base_fee_bps + (surplus * remaining / self.launch_fee_duration as i128) as u16
+    }
+
+    /// Check curve trading is open at `now`
+    pub fn check_trading_open(&self, now: i64) -> Result<()> {
+        require!(now >= self.launch_time, LaunchpadError::LaunchNotStarted);
+        require!(!self.is_expired(now), LaunchpadError::DeadlinePassed);
+        Ok(())
+    }
+
+    /// Whether the curve has raised its target or sold out, ending curve trading
+    pub fn is_complete(&self) -> bool {
+        self.sol_raised >= self.total_fund_raising || self.tokens_sold >= self.total_sell_amount
+    }
+
+    /// Fraction of the sell amount that `tokens_sold` represents, rounded down
+    fn progress_at(&self, tokens_sold: u64) -> Result<Q64x64> {
+        Q64x64::from_ratio(tokens_sold as u128, self.total_sell_amount as u128, Rounding::Down)
+            .ok_or(error!(LaunchpadError::ArithmeticOverflow))
+    }
+
+    /// Calculate the token price in lamports per base unit after `tokens_sold` are sold
+    ///
+    /// `curve_points` are the breakpoints from the launch's `PiecewiseCurve` account and are
+    /// ignored by the other curve types.
+    pub fn calculate_price(&self, tokens_sold: u64, curve_points: &[CurvePoint]) -> Result<Q64x64> {
+        if self.total_sell_amount == 0 {
+            return Ok(Q64x64::ZERO);
+        }
+
+        let progress = self.progress_at(tokens_sold)?;
+        let base_price = Q64x64::from_ratio(
+            self.total_fund_raising as u128,
+            self.total_sell_amount as u128,
+            Rounding::Down,
+        ).ok_or(LaunchpadError::ArithmeticOverflow)?;
+
+        let price = match self.curve_type {
+            CurveType::Linear => {
+                // Price = base_price * (1 + u)
+                Q64x64::ONE.checked_add(progress)
+                    .and_then(|multiplier| base_price.checked_mul(multiplier, Rounding::Down))
+            }
+            CurveType::Exponential => {
+                // Price = base_price * (1 + u)^2
+                Q64x64::ONE.checked_add(progress)
+                    .and_then(|multiplier| multiplier.pow(2, Rounding::Down))
+                    .and_then(|multiplier| base_price.checked_mul(multiplier, Rounding::Down))
+            }
+            CurveType::Logarithmic => {
+                // Price = base_price * (1 + g(u)) where g(u) = u up to 10% progress and
+                // 0.1 + (u - 0.1) / 2 after it, so growth halves past the knee
+                let knee = Self::logarithmic_knee()?;
+                progress.saturating_sub(knee).div_int(2, Rounding::Down)
+                    .and_then(|slowed| slowed.checked_add(progress.min(knee)))
+                    .and_then(|growth| Q64x64::ONE.checked_add(growth))
+                    .and_then(|multiplier| base_price.checked_mul(multiplier, Rounding::Down))
+            }
+            CurveType::ConstantProduct => {
+                // Price = SOL reserve / token reserve on the virtual reserves
+                let sol_reserve = (self.virtual_sol_reserves as u128)
+                    .checked_add(self.curve_reserve(tokens_sold, curve_points)? as u128)
+                    .ok_or(LaunchpadError::ArithmeticOverflow)?;
+                let token_reserve = (self.virtual_token_reserves as u128)
+                    .checked_sub(tokens_sold as u128)
+                    .ok_or(LaunchpadError::ArithmeticUnderflow)?;
+                Q64x64::from_ratio(sol_reserve, token_reserve, Rounding::Down)
+            }
+            CurveType::Piecewise => {
+                // Price interpolated linearly between the breakpoints around tokens_sold
+                let (_, segment) = self.piecewise_segment(curve_points, tokens_sold)?;
+                segment.price_at(tokens_sold - segment.start)
+            }
+        };
+
+        price.ok_or(error!(LaunchpadError::ArithmeticOverflow))
+    }
+
+    /// Progress at which the Logarithmic curve's growth slows down (10%)
+    fn logarithmic_knee() -> Result<Q64x64> {
+        Q64x64::from_ratio(1, 10, Rounding::Down).ok_or(error!(LaunchpadError::ArithmeticOverflow))
     }
 
-    /// Calculate current token price based on curve type
-    pub fn calculate_price(&self, tokens_sold: u64) -> Result<u64> {
+    /// Calculate the lamports absorbed by the curve once `tokens_sold` tokens are sold,
+    /// i.e. the area under `calculate_price` between zero and `tokens_sold`.
+    ///
+    /// Every curve is evaluated with roundings that never let this value decrease as
+    /// `tokens_sold` grows, so trade costs taken as differences of it are never negative
+    /// and add up exactly no matter how an order is split.
+    pub fn curve_reserve(&self, tokens_sold: u64, curve_points: &[CurvePoint]) -> Result<u64> {
+        require!(tokens_sold <= self.total_sell_amount, LaunchpadError::InsufficientTokens);
+
         if self.total_sell_amount == 0 {
             return Ok(0);
         }
 
-        let progress = (tokens_sold * 1_000_000) / self.total_sell_amount; // Progress in millionths
+        let reserve = match self.curve_type {
+            CurveType::ConstantProduct => {
+                // x * y = k on the virtual reserves: SOL in = k / (virtual tokens left) - virtual SOL,
+                // with the division rounded up in the curve's favor
+                let virtual_sol = self.virtual_sol_reserves as u128;
+                let virtual_tokens = self.virtual_token_reserves as u128;
+                let tokens_left = virtual_tokens
+                    .checked_sub(tokens_sold as u128)
+                    .filter(|left| *left > 0)
+                    .ok_or(LaunchpadError::InsufficientTokens)?;
 
-        match self.curve_type {
+                mul_div(virtual_sol, virtual_tokens, tokens_left, Rounding::Up)
+                    .and_then(|sol_reserve| sol_reserve.checked_sub(virtual_sol))
+            }
+            CurveType::Piecewise => {
+                // Trapezoids of the full segments below tokens_sold plus the partial one
+                let (absorbed, segment) = self.piecewise_segment(curve_points, tokens_sold)?;
+                segment.area_to(tokens_sold - segment.start)
+                    .and_then(|area| area.checked_add(absorbed))
+            }
+            _ => {
+                // Area under the price in units of F: integral of price / base_price over
+                // progress, every product rounded down
+                let progress = self.progress_at(tokens_sold)?;
+                self.progress_area(progress)?
+                    .mul_int(self.total_fund_raising as u128, Rounding::Down)
+            }
+        };
+
+        reserve
+            .and_then(|reserve| u64::try_from(reserve).ok())
+            .ok_or(error!(LaunchpadError::ArithmeticOverflow))
+    }
+
+    /// Area under `calculate_price / base_price` between zero and `progress`
+    fn progress_area(&self, progress: Q64x64) -> Result<Q64x64> {
+        let squared = progress.checked_mul(progress, Rounding::Down)
+            .ok_or(LaunchpadError::ArithmeticOverflow)?;
+
+        let area = match self.curve_type {
             CurveType::Linear => {
-                // Linear: price increases proportionally with progress
-                // Price = (total_fund_raising / total_sell_amount) * (1 + progress)
-                let base_price = (self.total_fund_raising * 1_000_000) / self.total_sell_amount;
-                Ok(base_price + (base_price * progress) / 1_000_000)
+                // (1 + u)  =>  u + u^2 / 2
+                squared.div_int(2, Rounding::Down)
+                    .and_then(|half| progress.checked_add(half))
             }
             CurveType::Exponential => {
-                // Exponential: price grows faster as more tokens are sold
-                // Simplified exponential: Price = base_price * (1 + progress)^2
-                let base_price = (self.total_fund_raising * 1_000_000) / self.total_sell_amount;
-                let multiplier = 1_000_000 + progress;
-                let squared = (multiplier * multiplier) / 1_000_000;
-                Ok((base_price * squared) / 1_000_000)
+                // (1 + u)^2  =>  u + u^2 + u^3 / 3
+                progress.pow(3, Rounding::Down)
+                    .and_then(|cubed| cubed.div_int(3, Rounding::Down))
+                    .and_then(|third| third.checked_add(squared))
+                    .and_then(|area| area.checked_add(progress))
             }
             CurveType::Logarithmic => {
-                // Logarithmic: price increases slowly at first, then faster
-                // Simplified: Price = base_price * log(1 + progress)
-                // Using approximation for log
-                let base_price = (self.total_fund_raising * 1_000_000) / self.total_sell_amount;
-                let log_approx = if progress < 100_000 {
-                    progress
-                } else {
-                    100_000 + ((progress - 100_000) / 2)
-                };
-                Ok((base_price * (1_000_000 + log_approx)) / 1_000_000)
+                // (1 + g(u)) integrated on each side of the knee:
+                // u + before^2 / 2 + after / 10 + after^2 / 4
+                let knee = Self::logarithmic_knee()?;
+                let before = progress.min(knee);
+                let after = progress.saturating_sub(knee);
+
+                let terms = [
+                    before.checked_mul(before, Rounding::Down).and_then(|v| v.div_int(2, Rounding::Down)),
+                    after.div_int(10, Rounding::Down),
+                    after.checked_mul(after, Rounding::Down).and_then(|v| v.div_int(4, Rounding::Down)),
+                ];
+                terms.iter().try_fold(progress, |area, term| area.checked_add((*term)?))
             }
+            CurveType::ConstantProduct | CurveType::Piecewise => None,
+        };
+
+        area.ok_or(error!(LaunchpadError::ArithmeticOverflow))
+    }
+
+    /// Piecewise segments in order, each with the lamports absorbed by all segments before it
+    fn piecewise_segments(&self, curve_points: &[CurvePoint]) -> Result<Vec<(u128, PiecewiseSegment)>> {
+        require!(curve_points.len() >= 2, LaunchpadError::MissingCurvePoints);
+
+        let mut segments = Vec::with_capacity(curve_points.len() - 1);
+        let mut absorbed = 0u128;
+        for pair in curve_points.windows(2) {
+            let segment = PiecewiseSegment::new(self.total_sell_amount, &pair[0], &pair[1])
+                .ok_or(LaunchpadError::InvalidCurvePoints)?;
+            let area = segment.area_to(segment.length).ok_or(LaunchpadError::ArithmeticOverflow)?;
+            segments.push((absorbed, segment));
+            absorbed = absorbed.checked_add(area).ok_or(LaunchpadError::ArithmeticOverflow)?;
         }
+
+        Ok(segments)
+    }
+
+    /// Locate the Piecewise segment containing `tokens_sold`, returning it together with
+    /// the lamports absorbed by all segments before it
+    fn piecewise_segment(&self, curve_points: &[CurvePoint], tokens_sold: u64) -> Result<(u128, PiecewiseSegment)> {
+        let mut segments = self.piecewise_segments(curve_points)?;
+
+        // A breakpoint belongs to the segment it ends, anything past the last one to that one
+        let index = segments
+            .partition_point(|(_, segment)| segment.start + segment.length < tokens_sold)
+            .min(segments.len() - 1);
+        Ok(segments.swap_remove(index))
+    }
+
+    /// Largest Piecewise position whose `curve_reserve` is at most `reserve`: the segment
+    /// holding it comes from the cumulative areas, then the search only runs inside it
+    fn piecewise_position_for_reserve(&self, reserve: u64, curve_points: &[CurvePoint]) -> Result<u64> {
+        let segments = self.piecewise_segments(curve_points)?;
+        let reserve = reserve as u128;
+
+        // Last segment absorbing no more than `reserve` before it starts, the first always does
+        let index = segments.partition_point(|(absorbed, _)| *absorbed <= reserve) - 1;
+        let (absorbed, segment) = &segments[index];
+
+        let (mut low, mut high) = (0, segment.length);
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            let area = segment.area_to(mid)
+                .and_then(|area| area.checked_add(*absorbed))
+                .ok_or(LaunchpadError::ArithmeticOverflow)?;
+            if area <= reserve {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+
+        Ok(segment.start + low)
+    }
+
+    /// Validate Piecewise breakpoints against this launch
+    ///
+    /// Requires 2 to `PiecewiseCurve::MAX_POINTS` points running from 0% to 100% progress
+    /// with every segment covering at least one token unit, a positive starting price that
+    /// never decreases, and a full-curve area that raises `total_fund_raising` without
+    /// overshooting it by more than `PiecewiseCurve::MAX_OVERSHOOT_BPS`.
+    pub fn validate_curve_points(&self, curve_points: &[CurvePoint]) -> Result<()> {
+        require!(
+            curve_points.len() >= 2 && curve_points.len() <= PiecewiseCurve::MAX_POINTS,
+            LaunchpadError::InvalidCurvePoints
+        );
+
+        let first = &curve_points[0];
+        let last = &curve_points[curve_points.len() - 1];
+        require!(
+            first.progress_bps == 0 && last.progress_bps == PiecewiseCurve::FULL_PROGRESS_BPS && first.price > 0,
+            LaunchpadError::InvalidCurvePoints
+        );
+
+        for pair in curve_points.windows(2) {
+            let segment = PiecewiseSegment::new(self.total_sell_amount, &pair[0], &pair[1])
+                .ok_or(LaunchpadError::InvalidCurvePoints)?;
+            require!(segment.length > 0, LaunchpadError::InvalidCurvePoints);
+        }
+
+        let raised = self.curve_reserve(self.total_sell_amount, curve_points)?;
+        let max_raised = (self.total_fund_raising as u128)
+            * (10_000 + PiecewiseCurve::MAX_OVERSHOOT_BPS as u128) / 10_000;
+        require!(
+            raised >= self.total_fund_raising && (raised as u128) <= max_raised,
+            LaunchpadError::InvalidFundRaisingTarget
+        );
+
+        Ok(())
+    }
+
+    /// Calculate SOL needed to buy `token_amount` tokens from the current curve position
+    pub fn calculate_buy_cost(&self, token_amount: u64, curve_points: &[CurvePoint]) -> Result<u64> {
+        let end = self.tokens_sold
+            .checked_add(token_amount)
+            .ok_or(LaunchpadError::ArithmeticOverflow)?;
+
+        self.curve_reserve(end, curve_points)?
+            .checked_sub(self.curve_reserve(self.tokens_sold, curve_points)?)
+            .ok_or(error!(LaunchpadError::ArithmeticUnderflow))
     }
 
     /// Calculate tokens to receive for given SOL amount
-    pub fn calculate_tokens_for_sol(&self, sol_amount: u64) -> Result<u64> {
-        // Simplified calculation - in production, this should integrate over the curve
-        let current_price = self.calculate_price(self.tokens_sold)?;
-        if current_price == 0 {
-            return Ok(0);
+    ///
+    /// Returns the largest amount whose `calculate_buy_cost` fits in `sol_amount`; any
+    /// remainder smaller than the price of one more token stays with the curve.
+    pub fn calculate_tokens_for_sol(&self, sol_amount: u64, curve_points: &[CurvePoint]) -> Result<u64> {
+        let budget = self.curve_reserve(self.tokens_sold, curve_points)?
+            .checked_add(sol_amount)
+            .ok_or(LaunchpadError::ArithmeticOverflow)?;
+
+        require!(
+            budget <= self.curve_reserve(self.total_sell_amount, curve_points)?,
+            LaunchpadError::InsufficientTokens
+        );
+
+        let position = self.position_for_reserve(budget, self.tokens_sold, self.total_sell_amount, curve_points)?;
+        Ok(position - self.tokens_sold)
+    }
+
+    /// Calculate tokens to sell to receive at least `sol_amount` back
+    ///
+    /// Returns the smallest amount whose `calculate_sol_for_tokens` covers `sol_amount`; the
+    /// part of its curve value above `sol_amount` stays with the curve.
+    pub fn calculate_tokens_for_sol_out(&self, sol_amount: u64, curve_points: &[CurvePoint]) -> Result<u64> {
+        let target = self.curve_reserve(self.tokens_sold, curve_points)?
+            .checked_sub(sol_amount)
+            .ok_or(LaunchpadError::InsufficientSOL)?;
+
+        let position = self.position_for_reserve(target, 0, self.tokens_sold, curve_points)?;
+        Ok(self.tokens_sold - position)
+    }
+
+    /// Find the largest position in `low..=high` whose `curve_reserve` is at most `reserve`,
+    /// given that `curve_reserve(low)` already is
+    fn position_for_reserve(&self, reserve: u64, low: u64, high: u64, curve_points: &[CurvePoint]) -> Result<u64> {
+        if self.curve_type == CurveType::ConstantProduct {
+            // Closed form: keep at least k / (virtual SOL + reserve) virtual tokens in the pool
+            let virtual_sol = self.virtual_sol_reserves as u128;
+            let virtual_tokens = self.virtual_token_reserves as u128;
+            let min_tokens_left = mul_div(virtual_sol, virtual_tokens, virtual_sol + reserve as u128, Rounding::Up)
+                .ok_or(LaunchpadError::ArithmeticOverflow)?;
+            let position = virtual_tokens.saturating_sub(min_tokens_left) as u64;
+            return Ok(position.clamp(low, high));
+        }
+
+        if self.curve_type == CurveType::Piecewise {
+            let position = self.piecewise_position_for_reserve(reserve, curve_points)?;
+            return Ok(position.clamp(low, high));
         }
-        Ok((sol_amount * 1_000_000) / current_price)
+
+        // Narrow the search to the units around the closed-form inverse, moving each bound
+        // only once the reserve on that side of the window confirms it. The position sought
+        // is the last one before the area reaches the next lamport.
+        let (mut low, mut high) = (low, high);
+        if let Some(estimate) = self.inverse_curve_reserve(reserve as u128 + 1) {
+            let below = estimate.saturating_sub(Self::INVERSE_SLACK).clamp(low, high);
+            let above = estimate.saturating_add(Self::INVERSE_SLACK).clamp(low, high);
+            if self.curve_reserve(below, curve_points)? <= reserve {
+                low = below;
+            }
+            if above < high && self.curve_reserve(above + 1, curve_points)? > reserve {
+                high = above;
+            }
+        }
+
+        // Binary search, the reserve never decreases as the position grows
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            if self.curve_reserve(mid, curve_points)? <= reserve {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+
+        Ok(low)
+    }
+
+    /// Position where the curve's exact area reaches `reserve`, for the progress-based
+    /// curves whose area inverts in closed form
+    ///
+    /// Only an estimate: the fixed-point roundings can put it a few units either side of
+    /// the position `curve_reserve` rounds to, and `None` just means searching without it.
+    fn inverse_curve_reserve(&self, reserve: u128) -> Option<u64> {
+        let area = Q64x64::from_ratio(reserve, self.total_fund_raising as u128, Rounding::Down)?;
+
+        let progress = match self.curve_type {
+            CurveType::Linear => Self::inverse_linear_area(area),
+            CurveType::Exponential => {
+                // u + u^2 + u^3 / 3 = A  =>  (1 + u)^3 = 1 + 3A  =>  u = e^(ln(1 + 3A) / 3) - 1
+                area.checked_mul(Q64x64::from_int(3), Rounding::Down)
+                    .and_then(|tripled| Q64x64::ONE.checked_add(tripled))
+                    .and_then(|cube| cube.ln())
+                    .and_then(|log| log.div_int(3, Rounding::Down))
+                    .and_then(|log| log.exp())
+                    .map(|root| root.saturating_sub(Q64x64::ONE))
+            }
+            CurveType::Logarithmic => {
+                let knee = Self::logarithmic_knee().ok()?;
+                let knee_area = self.progress_area(knee).ok()?;
+                if area <= knee_area {
+                    Self::inverse_linear_area(area)
+                } else {
+                    // Past the knee, with v = u - k and D = A - area(k):
+                    // v^2 / 4 + (1 + k) v = D  =>  v = 2 (sqrt((1 + k)^2 + D) - (1 + k))
+                    let slope = Q64x64::ONE.checked_add(knee)?;
+                    slope.checked_mul(slope, Rounding::Down)
+                        .and_then(|squared| squared.checked_add(area.saturating_sub(knee_area)))
+                        .and_then(|discriminant| discriminant.sqrt(Rounding::Down))
+                        .map(|root| root.saturating_sub(slope))
+                        .and_then(|half| half.checked_add(half))
+                        .and_then(|after| after.checked_add(knee))
+                }
+            }
+            CurveType::ConstantProduct | CurveType::Piecewise => None,
+        }?;
+
+        progress.mul_int(self.total_sell_amount as u128, Rounding::Down)
+            .and_then(|position| u64::try_from(position).ok())
     }
 
-    /// Calculate SOL needed for given token amount
-    pub fn calculate_sol_for_tokens(&self, token_amount: u64) -> Result<u64> {
-        let current_price = self.calculate_price(self.tokens_sold)?;
-        Ok((token_amount * current_price) / 1_000_000)
+    /// Progress where `u + u^2 / 2`, the Linear area, reaches `area`: u = sqrt(1 + 2A) - 1
+    fn inverse_linear_area(area: Q64x64) -> Option<Q64x64> {
+        area.checked_add(area)
+            .and_then(|doubled| Q64x64::ONE.checked_add(doubled))
+            .and_then(|discriminant| discriminant.sqrt(Rounding::Down))
+            .map(|root| root.saturating_sub(Q64x64::ONE))
+    }
+
+    /// Position at which the curve reaches `total_fund_raising` and completes, or the sell
+    /// amount if it only gets there by selling out
+    pub fn goal_position(&self, curve_points: &[CurvePoint]) -> Result<u64> {
+        let below_goal = self.total_fund_raising.saturating_sub(1);
+        let position = self.position_for_reserve(below_goal, 0, self.total_sell_amount, curve_points)?;
+        Ok(position.saturating_add(1).min(self.total_sell_amount))
+    }
+
+    /// Tokens the pool needs to pair with the SOL raised at `tokens_sold` to open at the
+    /// curve's price there
+    ///
+    /// Launches complete once the goal is raised, usually well before selling out, so the
+    /// pool is priced at the position the curve stopped at rather than its final price.
+    /// ConstantProduct launches are sized so this is exactly the supply left after the curve.
+    pub fn migration_token_amount(&self, tokens_sold: u64, curve_points: &[CurvePoint]) -> Result<u64> {
+        if self.curve_type == CurveType::ConstantProduct {
+            return self.total_supply
+                .checked_sub(self.total_sell_amount)
+                .ok_or(error!(LaunchpadError::InvalidSellAmount));
+        }
+
+        let raised = self.curve_reserve(tokens_sold, curve_points)?;
+        let price = self.calculate_price(tokens_sold, curve_points)?;
+        Q64x64::from_int(raised)
+            .checked_div(price, Rounding::Up)
+            .and_then(|tokens| tokens.to_int(Rounding::Up))
+            .ok_or(error!(LaunchpadError::ArithmeticOverflow))
+    }
+
+    /// Tokens create_launch sets aside for the migration pool: the most the pool can need at
+    /// any position the curve may complete at, from the goal position up to selling out
+    ///
+    /// Within a linear stretch of price the need first falls then rises, and it only rises on
+    /// the Exponential curve, so its peak is at the goal, a breakpoint or the sell amount.
+    /// Fails with `PoolUnderfunded` if that's more than the supply left after the curve, such
+    /// a pool would have to open above the curve's price.
+    pub fn migration_allocation(&self, curve_points: &[CurvePoint]) -> Result<u64> {
+        let remaining_supply = self.total_supply
+            .checked_sub(self.total_sell_amount)
+            .ok_or(LaunchpadError::InvalidSellAmount)?;
+        let goal_position = self.goal_position(curve_points)?;
+
+        let mut positions = vec![goal_position, self.total_sell_amount];
+        match self.curve_type {
+            CurveType::Logarithmic => positions.push(self.total_sell_amount / 10),
+            CurveType::Piecewise => positions.extend(curve_points.iter().map(|point| {
+                (self.total_sell_amount as u128 * point.progress_bps as u128
+                    / PiecewiseCurve::FULL_PROGRESS_BPS as u128) as u64
+            })),
+            _ => {}
+        }
+
+        let mut allocation = 0;
+        for position in positions.into_iter().filter(|position| *position >= goal_position) {
+            allocation = allocation.max(self.migration_token_amount(position, curve_points)?);
+        }
+
+        require!(allocation <= remaining_supply, LaunchpadError::PoolUnderfunded);
+        Ok(allocation)
+    }
+
+    /// Tokens and SOL to seed the migration pool with out of the `available_tokens` set aside
+    /// for it, opening the pool at the curve's price where it stopped
+    ///
+    /// All of `sol_raised` goes into the pool, it belongs to the buyers. `migration_allocation`
+    /// covers the curve's reserve wherever it stopped, buys only leave `sol_raised` above that
+    /// by remainders worth less than a token unit each, so capping at `available_tokens` moves
+    /// the price by no more than that dust.
+    pub fn migration_pool(&self, available_tokens: u64, curve_points: &[CurvePoint]) -> Result<(u64, u64)> {
+        let price = self.calculate_price(self.tokens_sold, curve_points)?;
+        let pool_tokens = Q64x64::from_int(self.sol_raised)
+            .checked_div(price, Rounding::Up)
+            .and_then(|tokens| tokens.to_int(Rounding::Up))
+            .ok_or(LaunchpadError::ArithmeticOverflow)?
+            .min(available_tokens);
+
+        Ok((pool_tokens, self.sol_raised))
+    }
+
+    /// Derive the ConstantProduct virtual reserves `(virtual_sol, virtual_tokens)`.
+    ///
+    /// Chosen so that selling `total_sell_amount` raises `total_fund_raising` and the final
+    /// curve price equals the price of a pool seeded with the remaining supply and the raised
+    /// SOL, i.e. `F / R` where `R = total_supply - total_sell_amount`. Solving both gives
+    /// `virtual_tokens = S^2 / (S - R)` and `virtual_sol = R * F / (S - R)`; the SOL side is
+    /// rounded up so the full curve never raises less than the target.
+    pub fn constant_product_reserves(
+        total_supply: u64,
+        total_sell_amount: u64,
+        total_fund_raising: u64,
+    ) -> Result<(u64, u64)> {
+        let supply = total_sell_amount as u128;
+        let pool_tokens = total_supply
+            .checked_sub(total_sell_amount)
+            .ok_or(LaunchpadError::InvalidSellAmount)? as u128;
+        require!(pool_tokens > 0 && supply > pool_tokens, LaunchpadError::InvalidSellAmount);
+
+        let spread = supply - pool_tokens;
+        let virtual_tokens = mul_div(supply, supply, spread, Rounding::Down)
+            .and_then(|reserve| u64::try_from(reserve).ok())
+            .ok_or(LaunchpadError::InvalidSupply)?;
+        let virtual_sol = mul_div(pool_tokens, total_fund_raising as u128, spread, Rounding::Up)
+            .and_then(|reserve| u64::try_from(reserve).ok())
+            .ok_or(LaunchpadError::InvalidFundRaisingTarget)?;
+
+        Ok((virtual_sol, virtual_tokens))
+    }
+
+    /// Calculate SOL returned for selling `token_amount` tokens back to the curve
+    ///
+    /// Walks the same `curve_reserve` backwards from `tokens_sold`, so selling what was just
+    /// bought returns exactly its curve cost and never more. Since buys add at least
+    /// `calculate_buy_cost` to `sol_raised`, `sol_raised` never falls below
+    /// `curve_reserve(tokens_sold)` and every sell can be paid.
+    pub fn calculate_sol_for_tokens(&self, token_amount: u64, curve_points: &[CurvePoint]) -> Result<u64> {
+        let start = self.tokens_sold
+            .checked_sub(token_amount)
+            .ok_or(LaunchpadError::InsufficientTokens)?;
+
+        self.curve_reserve(self.tokens_sold, curve_points)?
+            .checked_sub(self.curve_reserve(start, curve_points)?)
+            .ok_or(error!(LaunchpadError::ArithmeticUnderflow))
     }
 }
 
@@ -135,6 +784,210 @@ pub enum CurveType {
     Linear,
     Exponential,
     Logarithmic,
+    ConstantProduct,   // x * y = k on virtual reserves
+    Piecewise,         // Creator-defined breakpoints stored in a PiecewiseCurve account
+}
+
+/// Breakpoints of a creator-defined Piecewise curve
+#[account]
+pub struct PiecewiseCurve {
+    pub launch: Pubkey,
+    pub points: Vec<CurvePoint>,
+    pub bump: u8,
+}
+
+impl PiecewiseCurve {
+    /// Most breakpoints a curve may have; `tests/compute.rs` checks a buy across a curve
+    /// with this many stays inside the default compute budget
+    pub const MAX_POINTS: usize = 16;
+    pub const FULL_PROGRESS_BPS: u16 = 10_000;
+    pub const MAX_OVERSHOOT_BPS: u16 = 100;     // Full curve may raise up to 1% over target
+
+    pub const LEN: usize = 8 + 32 + 4 + Self::MAX_POINTS * CurvePoint::LEN + 1;
+}
+
+/// A (progress, price) breakpoint of a Piecewise curve
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct CurvePoint {
+    pub progress_bps: u16,      // Progress through total_sell_amount in basis points
+    pub price: u128,            // Q64.64 lamports per base unit
+}
+
+impl CurvePoint {
+    pub const LEN: usize = 2 + 16;
+}
+
+/// One linear piece of a Piecewise curve, positioned in tokens
+struct PiecewiseSegment {
+    start: u64,
+    length: u64,
+    start_price: Q64x64,
+    end_price: Q64x64,
+}
+
+impl PiecewiseSegment {
+    /// Build the segment between two breakpoints; `None` if they are out of order or the
+    /// price decreases
+    fn new(total_sell_amount: u64, from: &CurvePoint, to: &CurvePoint) -> Option<Self> {
+        let position = |point: &CurvePoint| {
+            mul_div(
+                total_sell_amount as u128,
+                point.progress_bps as u128,
+                PiecewiseCurve::FULL_PROGRESS_BPS as u128,
+                Rounding::Down,
+            ).map(|position| position as u64)
+        };
+
+        let start = position(from)?;
+        let end = position(to)?;
+        if to.progress_bps <= from.progress_bps || to.price < from.price {
+            return None;
+        }
+
+        Some(Self {
+            start,
+            length: end - start,
+            start_price: Q64x64::from_raw(from.price),
+            end_price: Q64x64::from_raw(to.price),
+        })
+    }
+
+    /// Price `offset` tokens into the segment
+    fn price_at(&self, offset: u64) -> Option<Q64x64> {
+        if self.length == 0 {
+            return Some(self.start_price);
+        }
+        let rise = self.end_price.checked_sub(self.start_price)?;
+        mul_div(rise.raw(), offset as u128, self.length as u128, Rounding::Down)
+            .and_then(|rise| self.start_price.checked_add(Q64x64::from_raw(rise)))
+    }
+
+    /// Lamports under the segment between its start and `offset` tokens into it:
+    /// start_price * d + (end_price - start_price) * d^2 / (2 * length), rounded down
+    fn area_to(&self, offset: u64) -> Option<u128> {
+        let flat = self.start_price.mul_int(offset as u128, Rounding::Down)?;
+        if self.length == 0 {
+            return Some(flat);
+        }
+        let rise = self.end_price.checked_sub(self.start_price)?;
+        let slope = mul_div(rise.raw(), offset as u128, 2 * self.length as u128, Rounding::Down)?;
+        let ramp = Q64x64::from_raw(slope).mul_int(offset as u128, Rounding::Down)?;
+        flat.checked_add(ramp)
+    }
+}
+
+/// Non-curve supply set aside for beneficiaries at create_launch, vesting from migration
+#[account]
+pub struct AllocationTable {
+    pub launch: Pubkey,
+    pub allocations: Vec<Allocation>,
+    pub bump: u8,
+}
+
+impl AllocationTable {
+    pub const MAX_ALLOCATIONS: usize = 8;
+
+    pub const LEN: usize = 8 + 32 + 4 + Self::MAX_ALLOCATIONS * Allocation::LEN + 1;
+
+    /// Check `allocations` fit in `available` tokens and return their total
+    pub fn validate_allocations(allocations: &[AllocationParams], available: u64) -> Result<u64> {
+        require!(
+            !allocations.is_empty() && allocations.len() <= Self::MAX_ALLOCATIONS,
+            LaunchpadError::InvalidAllocations
+        );
+
+        let mut total: u64 = 0;
+        for allocation in allocations {
+            require!(
+                allocation.amount > 0
+                    && allocation.beneficiary != Pubkey::default()
+                    && allocation.cliff_period >= 0
+                    && allocation.unlock_period >= 0,
+                LaunchpadError::InvalidAllocations
+            );
+            total = total
+                .checked_add(allocation.amount)
+                .ok_or(LaunchpadError::InvalidAllocations)?;
+        }
+
+        require!(total <= available, LaunchpadError::InvalidAllocations);
+
+        Ok(total)
+    }
+}
+
+/// Purpose of an allocation bucket
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AllocationKind {
+    Team,
+    Advisors,
+    Marketing,
+    Airdrop,
+}
+
+/// A beneficiary's bucket of supply with its own cliff and linear unlock
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Allocation {
+    pub kind: AllocationKind,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub cliff_period: i64,      // Seconds after migration before anything unlocks
+    pub unlock_period: i64,     // Seconds of linear unlock after the cliff
+    pub claimed: u64,           // Tokens released to the beneficiary so far
+}
+
+impl Allocation {
+    pub const LEN: usize = 1 + 32 + 8 + 8 + 8 + 8;
+}
+
+impl From<AllocationParams> for Allocation {
+    fn from(params: AllocationParams) -> Self {
+        Self {
+            kind: params.kind,
+            beneficiary: params.beneficiary,
+            amount: params.amount,
+            cliff_period: params.cliff_period,
+            unlock_period: params.unlock_period,
+            claimed: 0,
+        }
+    }
+}
+
+/// Allocation bucket requested at create_launch
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AllocationParams {
+    pub kind: AllocationKind,
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub cliff_period: i64,
+    pub unlock_period: i64,
+}
+
+/// Terms of a new launch, passed to create_launch
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CreateLaunchParams {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub decimals: u8,
+    pub supply: u64,
+    pub total_sell_amount: u64,
+    pub total_fund_raising: u64,
+    pub curve_type: CurveType,
+    pub curve_points: Vec<CurvePoint>,  // Breakpoints for Piecewise, empty otherwise
+    pub migrate_type: MigrateType,
+    pub cliff_period: i64,              // Creator vesting cliff after migration
+    pub unlock_period: i64,             // Creator vesting linear unlock after the cliff
+    pub start_time: Option<i64>,        // Trading opens now when None
+    pub deadline: Option<i64>,          // Launch fails if the goal isn't reached by then
+    pub allocations: Vec<AllocationParams>,
+}
+
+/// Allowlist entry a buyer proves during the presale phase
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PresaleProof {
+    pub allocation: u64,            // Max tokens bought during presale, 0 for uncapped
+    pub proof: Vec<[u8; 32]>,
 }
 
 /// Migration type when bonding curve completes
@@ -150,6 +1003,8 @@ pub enum LaunchStatus {
     Active,
     Migrated,
     Cancelled,
+    Completed,     // Goal reached or sold out, waiting for migration
+    Failed,        // Deadline passed short of the goal, buyers refunded their net SOL
 }
 
 /// Referral tracking
@@ -176,9 +1031,777 @@ pub struct UserPosition {
     pub tokens_sold: u64,
     pub sol_spent: u64,
     pub sol_received: u64,
+    pub sol_in_curve: u64,          // SOL put into the curve net of fees, less SOL taken out
     pub bump: u8,
 }
 
 impl UserPosition {
-    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 1;
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 1;
+
+    /// Tokens bought from the curve and not sold or returned since
+    pub fn net_tokens(&self) -> u64 {
+        self.tokens_bought.saturating_sub(self.tokens_sold)
+    }
+
+    /// Net SOL owed back for returning `token_amount` of `net_tokens` to a failed launch
+    pub fn net_sol_refund(&self, token_amount: u64) -> Result<u64> {
+        let net_tokens = self.net_tokens();
+        require!(token_amount <= net_tokens, LaunchpadError::InsufficientTokens);
+        if token_amount == 0 {
+            return Ok(0);
+        }
+
+        mul_div(self.sol_in_curve as u128, token_amount as u128, net_tokens as u128, Rounding::Down)
+            .map(|refund| refund as u64)
+            .ok_or(error!(LaunchpadError::ArithmeticOverflow))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUPPLY: u64 = 1_000_000_000_000_000_000; // 1B tokens with 9 decimals
+    const SELL_AMOUNT: u64 = 520_000_000_000_000_000; // 52% on the curve
+    const FUND_RAISING: u64 = 85_000_000_000; // 85 SOL
+
+    fn base_launch(curve_type: CurveType) -> Launch {
+        Launch {
+            creator: Pubkey::default(),
+            mint: Pubkey::default(),
+            name: String::new(),
+            symbol: String::new(),
+            uri: String::new(),
+            total_supply: SUPPLY,
+            total_sell_amount: SELL_AMOUNT,
+            total_fund_raising: FUND_RAISING,
+            tokens_sold: 0,
+            sol_raised: 0,
+            curve_type,
+            migrate_type: MigrateType::CPMM,
+            virtual_sol_reserves: 0,
+            virtual_token_reserves: 0,
+            status: LaunchStatus::Active,
+            creator_fee_earned: 0,
+            cliff_period: 0,
+            unlock_period: 0,
+            launch_time: 0,
+            deadline: 0,
+            sol_in_curve: 0,
+            presale_merkle_root: [0; 32],
+            presale_end_time: 0,
+            max_tokens_per_tx: 0,
+            max_tokens_per_wallet: 0,
+            purchase_caps_duration: 0,
+            launch_fee_bps: 0,
+            launch_fee_duration: 0,
+            vesting_amount: 0,
+            vesting_claimed: 0,
+            supply_finalized: false,
+            pending_creator: Pubkey::default(),
+            has_traded: false,
+            migrate_time: 0,
+            pool_address: Pubkey::default(),
+            bump: 0,
+        }
+    }
+
+    fn launch(curve_type: CurveType) -> Launch {
+        let mut launch = base_launch(curve_type.clone());
+        if curve_type == CurveType::ConstantProduct {
+            let (virtual_sol, virtual_tokens) =
+                Launch::constant_product_reserves(launch.total_supply, SELL_AMOUNT, FUND_RAISING).unwrap();
+            launch.virtual_sol_reserves = virtual_sol;
+            launch.virtual_token_reserves = virtual_tokens;
+        }
+        launch
+    }
+
+    fn all_curves() -> Vec<CurveType> {
+        vec![
+            CurveType::Linear,
+            CurveType::Exponential,
+            CurveType::Logarithmic,
+            CurveType::ConstantProduct,
+            CurveType::Piecewise,
+        ]
+    }
+
+    /// Price at `quarters / 4` of the base price, padded by one millionth so the curve
+    /// clears the target despite rounding down
+    fn piecewise_price(quarters: u128) -> u128 {
+        Q64x64::from_ratio(
+            FUND_RAISING as u128 * quarters * 1_000_001,
+            SELL_AMOUNT as u128 * 4 * 1_000_000,
+            Rounding::Up,
+        ).unwrap().raw()
+    }
+
+    /// Breakpoints whose trapezoids add up to the fund raising target:
+    /// 0.5x at 0%, 0.75x at 25%, 1.25x at 75% and 1.5x at 100% of the base price
+    fn points() -> Vec<CurvePoint> {
+        vec![
+            CurvePoint { progress_bps: 0, price: piecewise_price(2) },
+            CurvePoint { progress_bps: 2_500, price: piecewise_price(3) },
+            CurvePoint { progress_bps: 7_500, price: piecewise_price(5) },
+            CurvePoint { progress_bps: 10_000, price: piecewise_price(6) },
+        ]
+    }
+
+    #[test]
+    fn test_one_buy_costs_same_as_many_small_buys() {
+        for curve_type in all_curves() {
+            let mut launch = launch(curve_type);
+            launch.tokens_sold = SELL_AMOUNT / 20;
+
+            let total = SELL_AMOUNT / 2;
+            let single = launch.calculate_buy_cost(total, &points()).unwrap();
+
+            let step = total / 1_000;
+            let mut split = 0;
+            for _ in 0..1_000 {
+                split += launch.calculate_buy_cost(step, &points()).unwrap();
+                launch.tokens_sold += step;
+            }
+
+            assert_eq!(single, split);
+        }
+    }
+
+    #[test]
+    fn test_large_buy_pays_rising_price() {
+        for curve_type in all_curves() {
+            let launch = launch(curve_type.clone());
+            let half = SELL_AMOUNT / 2;
+
+            let first_half = launch.calculate_buy_cost(half, &points()).unwrap();
+            let whole = launch.calculate_buy_cost(SELL_AMOUNT, &points()).unwrap();
+
+            // The second half of the curve is more expensive than the first
+            assert!(whole - first_half > first_half);
+            // Progress-based curves never sell below the base price
+            if matches!(curve_type, CurveType::Linear | CurveType::Exponential | CurveType::Logarithmic) {
+                assert!(first_half >= FUND_RAISING / 2 - 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_curve_reserve_matches_closed_form() {
+        // Linear area over the whole curve is 1.5 * F, exponential is 7/3 * F
+        assert_eq!(launch(CurveType::Linear).curve_reserve(SELL_AMOUNT, &points()).unwrap(), FUND_RAISING * 3 / 2);
+        assert_eq!(
+            launch(CurveType::Exponential).curve_reserve(SELL_AMOUNT, &points()).unwrap(),
+            FUND_RAISING * 7 / 3
+        );
+        // Logarithmic: 1 + 0.005 + 0.09 + 0.2025, the inexact knee rounds down by a lamport
+        let logarithmic = launch(CurveType::Logarithmic).curve_reserve(SELL_AMOUNT, &points()).unwrap();
+        assert!(FUND_RAISING * 12_975 / 10_000 - logarithmic <= 1);
+    }
+
+    #[test]
+    fn test_curve_math_handles_large_supplies() {
+        // Piecewise breakpoints are absolute prices for the fixture launch
+        for curve_type in all_curves().into_iter().filter(|curve| *curve != CurveType::Piecewise) {
+            let mut launch = launch(curve_type.clone());
+            launch.total_sell_amount = u64::MAX / 2;
+            launch.total_supply = u64::MAX;
+            launch.total_fund_raising = u64::MAX / 2;
+            if curve_type == CurveType::ConstantProduct {
+                // Virtual token reserves for this split no longer fit in a u64
+                assert!(Launch::constant_product_reserves(
+                    launch.total_supply,
+                    launch.total_sell_amount,
+                    launch.total_fund_raising,
+                ).is_err());
+                continue;
+            }
+
+            let start = launch.calculate_price(0, &points()).unwrap();
+            let end = launch.calculate_price(launch.total_sell_amount, &points()).unwrap();
+            assert!(end > start);
+            assert_eq!(launch.get_progress(), 0);
+
+            // The full area of the exponential curve (7/3 F) no longer fits in lamports and
+            // is reported rather than wrapped
+            let reserve = launch.curve_reserve(launch.total_sell_amount, &points());
+            if curve_type == CurveType::Exponential {
+                assert!(reserve.is_err());
+            } else {
+                assert!(reserve.unwrap() >= launch.total_fund_raising);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tokens_for_sol_inverts_buy_cost() {
+        for curve_type in all_curves() {
+            let mut launch = launch(curve_type);
+            launch.tokens_sold = SELL_AMOUNT / 3;
+
+            let sol_amount = 5_000_000_000;
+            let tokens = launch.calculate_tokens_for_sol(sol_amount, &points()).unwrap();
+
+            assert!(launch.calculate_buy_cost(tokens, &points()).unwrap() <= sol_amount);
+            assert!(launch.calculate_buy_cost(tokens + 1, &points()).unwrap() > sol_amount);
+        }
+    }
+
+    /// Largest position whose reserve is at most `reserve`, found by searching alone
+    fn searched_position(launch: &Launch, reserve: u64) -> u64 {
+        let (mut low, mut high) = (0, launch.total_sell_amount);
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            if launch.curve_reserve(mid, &points()).unwrap() <= reserve {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        low
+    }
+
+    #[test]
+    fn test_inverse_lands_next_to_position() {
+        for curve_type in [CurveType::Linear, CurveType::Exponential, CurveType::Logarithmic] {
+            let mut large = launch(curve_type.clone());
+            large.total_sell_amount = u64::MAX / 2;
+            large.total_supply = u64::MAX;
+            large.total_fund_raising = u64::MAX / 4;
+            // Fewer token units than lamports, so a lamport buys less than a unit
+            let mut small = launch(curve_type.clone());
+            small.total_sell_amount = 1_000_000;
+
+            for launch in [launch(curve_type.clone()), large, small] {
+                // Logarithmic turns at under a tenth of the area
+                let full = launch.curve_reserve(launch.total_sell_amount, &points()).unwrap();
+                for reserve in [0, 1, 999, full / 20, full / 7, full / 2, full - 1, full] {
+                    let position = searched_position(&launch, reserve);
+                    let estimate = launch.inverse_curve_reserve(reserve as u128 + 1).unwrap();
+                    let off = estimate.min(launch.total_sell_amount).abs_diff(position);
+                    assert!(off < Launch::INVERSE_SLACK, "reserve {}", reserve);
+                    assert_eq!(
+                        launch.position_for_reserve(reserve, 0, launch.total_sell_amount, &points()).unwrap(),
+                        position
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_tokens_for_sol_rejects_buying_past_sell_amount() {
+        let mut launch = launch(CurveType::Linear);
+        launch.tokens_sold = SELL_AMOUNT - 1_000;
+
+        let remaining_cost = launch.calculate_buy_cost(1_000, &points()).unwrap();
+        assert_eq!(launch.calculate_tokens_for_sol(remaining_cost, &points()).unwrap(), 1_000);
+        assert!(launch.calculate_tokens_for_sol(remaining_cost + 1, &points()).is_err());
+    }
+
+    #[test]
+    fn test_constant_product_raises_target_and_matches_pool_price() {
+        let launch = launch(CurveType::ConstantProduct);
+        let pool_tokens = launch.total_supply - SELL_AMOUNT;
+
+        // Selling out raises the target, rounded in the curve's favor by at most a few lamports
+        let raised = launch.curve_reserve(SELL_AMOUNT, &points()).unwrap();
+        assert!(raised >= FUND_RAISING && raised - FUND_RAISING < 10);
+
+        // Final curve price lines up with a pool seeded with the raised SOL and remaining tokens
+        let final_price = launch.calculate_price(SELL_AMOUNT, &points()).unwrap();
+        let pool_price = Q64x64::from_ratio(FUND_RAISING as u128, pool_tokens as u128, Rounding::Down).unwrap();
+        assert!(final_price >= pool_price);
+        assert!(final_price.raw() - pool_price.raw() < pool_price.raw() / 1_000_000_000);
+    }
+
+    #[test]
+    fn test_constant_product_rejects_invalid_split() {
+        assert!(Launch::constant_product_reserves(1_000, 1_000, FUND_RAISING).is_err());
+        assert!(Launch::constant_product_reserves(1_000, 500, FUND_RAISING).is_err());
+        assert!(Launch::constant_product_reserves(1_000, 1_001, FUND_RAISING).is_err());
+    }
+
+    #[test]
+    fn test_piecewise_interpolates_between_breakpoints() {
+        let launch = launch(CurveType::Piecewise);
+        let points = points();
+
+        assert_eq!(launch.calculate_price(0, &points).unwrap().raw(), points[0].price);
+        assert_eq!(launch.calculate_price(SELL_AMOUNT / 4, &points).unwrap().raw(), points[1].price);
+        assert_eq!(launch.calculate_price(SELL_AMOUNT, &points).unwrap().raw(), points[3].price);
+
+        let midpoint = launch.calculate_price(SELL_AMOUNT / 2, &points).unwrap().raw();
+        assert!(midpoint.abs_diff((points[1].price + points[2].price) / 2) <= 1);
+    }
+
+    #[test]
+    fn test_piecewise_raises_target() {
+        let launch = launch(CurveType::Piecewise);
+        assert!(launch.validate_curve_points(&points()).is_ok());
+
+        // Trapezoids add up to the padded target, give or take rounding
+        let raised = launch.curve_reserve(SELL_AMOUNT, &points()).unwrap();
+        let padded = FUND_RAISING + FUND_RAISING / 1_000_000;
+        assert!(raised.abs_diff(padded) <= 4);
+
+        // First quarter: (0.5 + 0.75) / 2 * 25% of the target
+        let first_quarter = launch.curve_reserve(SELL_AMOUNT / 4, &points()).unwrap();
+        assert!(first_quarter.abs_diff(padded * 5 / 32) <= 2);
+    }
+
+    #[test]
+    fn test_piecewise_position_matches_search() {
+        let launch = launch(CurveType::Piecewise);
+        let full = launch.curve_reserve(SELL_AMOUNT, &points()).unwrap();
+
+        // Either side of every breakpoint as well as inside the segments
+        let mut reserves = vec![0, 1, full / 3, full - 1, full];
+        for position in [SELL_AMOUNT / 4, SELL_AMOUNT * 3 / 4] {
+            let at = launch.curve_reserve(position, &points()).unwrap();
+            reserves.extend([at - 1, at, at + 1]);
+        }
+
+        for reserve in reserves {
+            assert_eq!(
+                launch.position_for_reserve(reserve, 0, SELL_AMOUNT, &points()).unwrap(),
+                searched_position(&launch, reserve),
+                "reserve {}",
+                reserve
+            );
+        }
+    }
+
+    #[test]
+    fn test_piecewise_rejects_invalid_points() {
+        let launch = launch(CurveType::Piecewise);
+
+        // Decreasing price
+        let mut decreasing = points();
+        decreasing[2].price = decreasing[1].price - 1;
+        assert!(launch.validate_curve_points(&decreasing).is_err());
+
+        // Not starting at 0% or ending at 100%
+        assert!(launch.validate_curve_points(&points()[1..]).is_err());
+        assert!(launch.validate_curve_points(&points()[..3]).is_err());
+
+        // Out-of-order progress
+        let mut unordered = points();
+        unordered[2].progress_bps = 2_000;
+        assert!(launch.validate_curve_points(&unordered).is_err());
+
+        // Too many points
+        let mut crowded = vec![CurvePoint { progress_bps: 0, price: piecewise_price(2) }];
+        for step in 1..=PiecewiseCurve::MAX_POINTS as u32 {
+            let progress_bps = (step * 10_000 / PiecewiseCurve::MAX_POINTS as u32) as u16;
+            crowded.push(CurvePoint { progress_bps, price: piecewise_price(4) });
+        }
+        assert!(launch.validate_curve_points(&crowded).is_err());
+
+        // Raising too little or too much
+        let cheap: Vec<CurvePoint> = points().iter()
+            .map(|point| CurvePoint { price: point.price * 99 / 100, ..*point })
+            .collect();
+        assert!(launch.validate_curve_points(&cheap).is_err());
+        let expensive: Vec<CurvePoint> = points().iter()
+            .map(|point| CurvePoint { price: point.price * 102 / 100, ..*point })
+            .collect();
+        assert!(launch.validate_curve_points(&expensive).is_err());
+
+        // Pricing without the companion account
+        assert!(launch.curve_reserve(SELL_AMOUNT / 2, &[]).is_err());
+    }
+
+    fn buy(launch: &mut Launch, sol_amount: u64) -> u64 {
+        let tokens = launch.calculate_tokens_for_sol(sol_amount, &points()).unwrap();
+        launch.tokens_sold += tokens;
+        launch.sol_raised += sol_amount;
+        tokens
+    }
+
+    fn sell(launch: &mut Launch, token_amount: u64) -> u64 {
+        let sol = launch.calculate_sol_for_tokens(token_amount, &points()).unwrap();
+        launch.tokens_sold -= token_amount;
+        launch.sol_raised = launch.sol_raised.checked_sub(sol).unwrap();
+        sol
+    }
+
+    #[test]
+    fn test_buy_then_sell_is_never_profitable() {
+        for curve_type in all_curves() {
+            let mut launch = launch(curve_type);
+            buy(&mut launch, 20_000_000_000);
+
+            for sol_amount in [1, 999, 1_000_000, 3_333_333_333, 10_000_000_000] {
+                let tokens = buy(&mut launch, sol_amount);
+                assert!(sell(&mut launch, tokens) <= sol_amount);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sell_mirrors_buy_cost() {
+        for curve_type in all_curves() {
+            let mut launch = launch(curve_type);
+            launch.tokens_sold = SELL_AMOUNT / 4;
+
+            let cost = launch.calculate_buy_cost(SELL_AMOUNT / 10, &points()).unwrap();
+            launch.tokens_sold += SELL_AMOUNT / 10;
+            assert_eq!(launch.calculate_sol_for_tokens(SELL_AMOUNT / 10, &points()).unwrap(), cost);
+        }
+    }
+
+    #[test]
+    fn test_curve_stays_solvent() {
+        for curve_type in all_curves() {
+            let mut launch = launch(curve_type);
+            let mut holdings: Vec<u64> = Vec::new();
+            let mut seed: u64 = 42;
+
+            for _ in 0..500 {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                let roll = seed >> 33;
+
+                if !roll.is_multiple_of(3) || holdings.is_empty() {
+                    let sol_amount = 1 + roll % 2_000_000_000;
+                    if launch.calculate_tokens_for_sol(sol_amount, &points()).is_ok() {
+                        holdings.push(buy(&mut launch, sol_amount));
+                    }
+                } else {
+                    let index = (roll as usize) % holdings.len();
+                    let tokens = holdings.swap_remove(index);
+                    sell(&mut launch, tokens);
+                }
+
+                assert!(launch.sol_raised >= launch.curve_reserve(launch.tokens_sold, &points()).unwrap());
+            }
+
+            // Everyone can exit
+            for tokens in holdings {
+                sell(&mut launch, tokens);
+            }
+            assert_eq!(launch.tokens_sold, 0);
+        }
+    }
+
+    #[test]
+    fn test_sell_rejects_more_than_sold() {
+        let mut launch = launch(CurveType::Linear);
+        launch.tokens_sold = 1_000;
+        assert!(launch.calculate_sol_for_tokens(1_001, &points()).is_err());
+    }
+
+    #[test]
+    fn test_tokens_for_sol_out_sells_fewest_tokens() {
+        for curve_type in all_curves() {
+            let mut launch = launch(curve_type);
+            launch.tokens_sold = SELL_AMOUNT / 2;
+
+            for sol_amount in [1, 1_000_003, 1_000_000_000, FUND_RAISING / 5] {
+                let tokens = launch.calculate_tokens_for_sol_out(sol_amount, &points()).unwrap();
+                assert!(launch.calculate_sol_for_tokens(tokens, &points()).unwrap() >= sol_amount);
+                assert!(launch.calculate_sol_for_tokens(tokens - 1, &points()).unwrap() < sol_amount);
+            }
+
+            let reserve = launch.curve_reserve(launch.tokens_sold, &points()).unwrap();
+            let tokens = launch.calculate_tokens_for_sol_out(reserve, &points()).unwrap();
+            assert_eq!(launch.calculate_sol_for_tokens(tokens, &points()).unwrap(), reserve);
+            assert!(launch.calculate_tokens_for_sol_out(reserve + 1, &points()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_curve_completes_at_target_or_sell_out() {
+        for curve_type in all_curves() {
+            let mut launch = launch(curve_type);
+            buy(&mut launch, FUND_RAISING / 2);
+            assert!(!launch.is_complete());
+
+            // Buying whatever is left completes the curve on the same trade
+            let remaining_cost = launch.calculate_buy_cost(SELL_AMOUNT - launch.tokens_sold, &points()).unwrap();
+            buy(&mut launch, remaining_cost);
+            assert_eq!(launch.tokens_sold, SELL_AMOUNT);
+            assert!(launch.is_complete());
+        }
+    }
+
+    #[test]
+    fn test_launch_limits() {
+        let limits = LaunchLimits::DEFAULT;
+        assert!(limits.validate().is_ok());
+
+        // Same 51%-80% bounds create_launch used to hard-code
+        let supply = 1_000_000_000_000_000;
+        assert!(limits.check_sell_amount(supply, supply * 51 / 100).is_ok());
+        assert!(limits.check_sell_amount(supply, supply * 80 / 100).is_ok());
+        assert!(limits.check_sell_amount(supply, supply * 51 / 100 - 1).is_err());
+        assert!(limits.check_sell_amount(supply, supply * 80 / 100 + 1).is_err());
+
+        let invalid = [
+            LaunchLimits { min_sell_bps: 8100, ..limits },
+            LaunchLimits { max_sell_bps: 10_000, ..limits },
+            LaunchLimits { min_fund_raising: 0, ..limits },
+            LaunchLimits { max_fund_raising: limits.min_fund_raising - 1, ..limits },
+            LaunchLimits { max_decimals: LaunchLimits::MAX_DECIMALS + 1, ..limits },
+            LaunchLimits { max_uri_len: Launch::MAX_URI_LEN as u16 + 1, ..limits },
+        ];
+        for limits in invalid {
+            assert!(limits.validate().is_err(), "{:?}", limits);
+        }
+    }
+
+    #[test]
+    fn test_refunds_split_reserve_pro_rata() {
+        let mut launch = launch(CurveType::Linear);
+        let holdings = [buy(&mut launch, 3_000_000_007), buy(&mut launch, 1_234_567_891), buy(&mut launch, 999)];
+        let reserve = launch.sol_raised;
+
+        let mut refunded = 0;
+        for tokens in holdings {
+            let refund = launch.refund_for(tokens).unwrap();
+            launch.tokens_sold -= tokens;
+            launch.sol_raised -= refund;
+            refunded += refund;
+        }
+
+        // Everything comes back out, nothing more
+        assert_eq!(refunded, reserve);
+        assert_eq!(launch.sol_raised, 0);
+        assert!(launch.refund_for(1).is_err());
+    }
+
+    #[test]
+    fn test_net_sol_refund() {
+        let position = UserPosition {
+            user: Pubkey::default(),
+            launch: Pubkey::default(),
+            tokens_bought: 3_000,
+            tokens_sold: 1_000,
+            sol_spent: 10_100,
+            sol_received: 4_000,
+            sol_in_curve: 6_000,
+            bump: 0,
+        };
+
+        assert_eq!(position.net_tokens(), 2_000);
+        assert_eq!(position.net_sol_refund(2_000).unwrap(), 6_000);
+        assert_eq!(position.net_sol_refund(1_000).unwrap(), 3_000);
+        assert_eq!(position.net_sol_refund(1).unwrap(), 3);
+        assert!(position.net_sol_refund(2_001).is_err());
+    }
+
+    #[test]
+    fn test_net_refunds_split_short_reserve_pro_rata() {
+        // Profitable sells left 9_000 in the reserve against 12_000 of net SOL outstanding
+        let mut launch = launch(CurveType::Linear);
+        launch.sol_raised = 9_000;
+        launch.sol_in_curve = 12_000;
+
+        // Each gets three quarters of its net SOL, the last one the rounding left over
+        for (net_sol, expected) in [(7_001, 5_250), (4_999, 3_750)] {
+            let refund = launch.net_refund_for(net_sol).unwrap();
+            assert_eq!(refund, expected);
+            launch.sol_raised -= refund;
+            launch.sol_in_curve -= net_sol;
+        }
+
+        assert_eq!(launch.sol_raised, 0);
+        assert!(launch.net_refund_for(1).is_err());
+    }
+
+    #[test]
+    fn test_trading_window() {
+        let mut launch = launch(CurveType::Linear);
+        assert!(!launch.is_expired(i64::MAX));
+
+        launch.launch_time = 500;
+        launch.deadline = 1_000;
+        assert!(launch.check_trading_open(499).is_err());
+        assert!(launch.check_trading_open(500).is_ok());
+        assert!(launch.check_trading_open(999).is_ok());
+        assert!(launch.check_trading_open(1_000).is_err());
+
+        launch.status = LaunchStatus::Completed;
+        assert!(!launch.is_expired(1_000));
+
+        assert!(launch.purchase_caps_active(i64::MAX));
+        launch.purchase_caps_duration = 60;
+        assert!(launch.purchase_caps_active(559));
+        assert!(!launch.purchase_caps_active(560));
+
+        launch.presale_end_time = 700;
+        assert!(!launch.is_presale(600));
+        launch.presale_merkle_root = [1; 32];
+        assert!(launch.is_presale(600));
+        assert!(!launch.is_presale(700));
+    }
+
+    #[test]
+    fn test_launch_fee_decays_to_base_fee() {
+        let mut launch = launch(CurveType::Linear);
+        launch.launch_time = 1_000;
+        assert_eq!(launch.fee_bps_at(100, 1_000), 100);
+
+        launch.launch_fee_bps = 5_000;
+        launch.launch_fee_duration = 60;
+        assert_eq!(launch.fee_bps_at(100, 1_000), 5_000);
+        assert_eq!(launch.fee_bps_at(100, 1_030), 2_550);
+        assert_eq!(launch.fee_bps_at(100, 1_059), 181);
+        assert_eq!(launch.fee_bps_at(100, 1_060), 100);
+        assert_eq!(launch.fee_bps_at(100, i64::MAX), 100);
+
+        // Never below the config fee, even if that was raised past the launch fee
+        assert_eq!(launch.fee_bps_at(6_000, 1_000), 6_000);
+    }
+
+    /// Assert a pool of `pool_tokens` out of `available` and `pool_sol` holds all the raised
+    /// SOL and opens at the curve's price where it stopped, give or take a billionth
+    fn assert_pool_price(launch: &Launch, available: u64, pool_tokens: u64, pool_sol: u64) {
+        assert_eq!(pool_sol, launch.sol_raised);
+        assert!(pool_tokens > 0 && pool_tokens <= available);
+
+        let curve_price = launch.calculate_price(launch.tokens_sold, &points()).unwrap();
+        let pool_price = Q64x64::from_ratio(pool_sol as u128, pool_tokens as u128, Rounding::Down).unwrap();
+        assert!(curve_price.raw().abs_diff(pool_price.raw()) <= curve_price.raw() / 1_000_000_000);
+    }
+
+    #[test]
+    fn test_migration_pool_opens_at_completion_price() {
+        for curve_type in all_curves() {
+            let mut launch = launch(curve_type.clone());
+            let allocation = launch.migration_allocation(&points()).unwrap();
+            assert!(allocation > 0 && allocation <= SUPPLY - SELL_AMOUNT);
+
+            let goal_position = launch.goal_position(&points()).unwrap();
+            for position in [goal_position, (goal_position + SELL_AMOUNT) / 2, SELL_AMOUNT] {
+                launch.tokens_sold = position;
+                launch.sol_raised = launch.curve_reserve(position, &points()).unwrap();
+
+                let (pool_tokens, pool_sol) = launch.migration_pool(allocation, &points()).unwrap();
+                assert_pool_price(&launch, allocation, pool_tokens, pool_sol);
+            }
+        }
+
+        // Selling out needs the most, 3/4 of the sell amount for Linear and ~84% for Logarithmic
+        let linear = launch(CurveType::Linear).migration_allocation(&points()).unwrap();
+        assert_eq!(linear / (SELL_AMOUNT / 1_000), 750);
+        let logarithmic = launch(CurveType::Logarithmic).migration_allocation(&points()).unwrap();
+        assert_eq!(logarithmic / (SELL_AMOUNT / 1_000), 837);
+        assert_eq!(
+            launch(CurveType::ConstantProduct).migration_allocation(&points()).unwrap(),
+            SUPPLY - SELL_AMOUNT
+        );
+    }
+
+    #[test]
+    fn test_migration_allocation_rejects_underfunded_pool() {
+        // With 70% sold, the built-in curves need more than the 30% left to pool the raised SOL
+        // at their price once they sell out, so such launches are turned away
+        for curve_type in [CurveType::Linear, CurveType::Exponential, CurveType::Logarithmic] {
+            let mut launch = base_launch(curve_type);
+            launch.total_supply = SELL_AMOUNT * 10 / 7;
+            assert!(launch.migration_token_amount(SELL_AMOUNT, &points()).unwrap() > launch.total_supply - SELL_AMOUNT);
+            assert_eq!(
+                launch.migration_allocation(&points()).unwrap_err(),
+                error!(LaunchpadError::PoolUnderfunded)
+            );
+        }
+
+        // A Piecewise curve can need the most at a breakpoint past the goal: a flat price up to
+        // 99.6% then a jump at the very end pools more tokens at 99.6% than at the goal or the end
+        let mut launch = base_launch(CurveType::Piecewise);
+        launch.total_supply = SELL_AMOUNT * 3;
+        let price = |numerator: u128| {
+            Q64x64::from_ratio(FUND_RAISING as u128 * numerator, SELL_AMOUNT as u128 * 992, Rounding::Up).unwrap().raw()
+        };
+        let jump = [
+            CurvePoint { progress_bps: 0, price: price(1_000) },
+            CurvePoint { progress_bps: 9_960, price: price(1_000) },
+            CurvePoint { progress_bps: 10_000, price: price(1_500) },
+        ];
+        launch.validate_curve_points(&jump).unwrap();
+        let breakpoint = SELL_AMOUNT / 10_000 * 9_960;
+        let goal_position = launch.goal_position(&jump).unwrap();
+        assert!(goal_position < breakpoint);
+
+        let at_breakpoint = launch.migration_token_amount(breakpoint, &jump).unwrap();
+        assert!(at_breakpoint > launch.migration_token_amount(goal_position, &jump).unwrap());
+        assert!(at_breakpoint > launch.migration_token_amount(SELL_AMOUNT, &jump).unwrap());
+        assert_eq!(launch.migration_allocation(&jump).unwrap(), at_breakpoint);
+    }
+
+    #[test]
+    fn test_goal_reached_before_sell_out() {
+        for curve_type in [CurveType::Linear, CurveType::Exponential, CurveType::Logarithmic] {
+            let mut launch = launch(curve_type);
+            let allocation = launch.migration_allocation(&points()).unwrap();
+            let goal_position = launch.goal_position(&points()).unwrap();
+            assert!(goal_position < SELL_AMOUNT);
+            assert!(launch.curve_reserve(goal_position, &points()).unwrap() >= FUND_RAISING);
+            assert!(launch.curve_reserve(goal_position - 1, &points()).unwrap() < FUND_RAISING);
+
+            // Raising the goal completes the curve around there, short of selling out
+            buy(&mut launch, FUND_RAISING);
+            assert!(launch.is_complete());
+            assert!(launch.tokens_sold >= goal_position && launch.tokens_sold < SELL_AMOUNT);
+
+            // The pool opens at the price the curve stopped at, below its final price
+            let stop_price = launch.calculate_price(launch.tokens_sold, &points()).unwrap();
+            assert!(stop_price < launch.calculate_price(SELL_AMOUNT, &points()).unwrap());
+
+            let (pool_tokens, pool_sol) = launch.migration_pool(allocation, &points()).unwrap();
+            assert_pool_price(&launch, allocation, pool_tokens, pool_sol);
+        }
+
+        // Roughly 73% of a Linear curve raises the goal
+        let goal_position = launch(CurveType::Linear).goal_position(&points()).unwrap();
+        assert_eq!(goal_position / (SELL_AMOUNT / 1_000), 732);
+    }
+
+    #[test]
+    fn test_overshooting_buy_keeps_pool_price() {
+        for curve_type in all_curves() {
+            let mut launch = launch(curve_type);
+            let allocation = launch.migration_allocation(&points()).unwrap();
+
+            // A single buy from zero that runs well past the goal position
+            let overshoot = launch.goal_position(&points()).unwrap() + SELL_AMOUNT / 10;
+            let overshoot = overshoot.min(SELL_AMOUNT);
+            let cost = launch.calculate_buy_cost(overshoot, &points()).unwrap();
+            buy(&mut launch, cost);
+            assert!(launch.is_complete());
+
+            // The pool needs more tokens than it would have at the goal, the allocation covers
+            // them and the pool still opens at the curve's price with all the SOL
+            let (pool_tokens, pool_sol) = launch.migration_pool(allocation, &points()).unwrap();
+            assert_pool_price(&launch, allocation, pool_tokens, pool_sol);
+        }
+    }
+
+    #[test]
+    fn test_validate_allocations() {
+        let allocation = AllocationParams {
+            kind: AllocationKind::Team,
+            beneficiary: Pubkey::new_unique(),
+            amount: 1_000,
+            cliff_period: 86_400,
+            unlock_period: 864_000,
+        };
+        let advisors = AllocationParams { kind: AllocationKind::Advisors, amount: 500, ..allocation };
+
+        assert_eq!(AllocationTable::validate_allocations(&[allocation, advisors], 1_500).unwrap(), 1_500);
+        assert!(AllocationTable::validate_allocations(&[allocation, advisors], 1_499).is_err());
+        assert!(AllocationTable::validate_allocations(&[], 1_500).is_err());
+        assert!(AllocationTable::validate_allocations(&[allocation; AllocationTable::MAX_ALLOCATIONS + 1], u64::MAX).is_err());
+
+        let invalid = [
+            AllocationParams { amount: 0, ..allocation },
+            AllocationParams { beneficiary: Pubkey::default(), ..allocation },
+            AllocationParams { cliff_period: -1, ..allocation },
+            AllocationParams { amount: u64::MAX, ..allocation },
+        ];
+        for bad in invalid {
+            assert!(AllocationTable::validate_allocations(&[allocation, bad], u64::MAX).is_err(), "{:?}", bad);
+        }
+    }
 }
@@ -1,61 +1,38 @@
-use anchor_lang::prelude::*;
+//! Utility functions for the launchpad program
 
-/// Utility functions for the launchpad program
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak::hashv;
 
-/// Calculate square root using Newton's method
-pub fn sqrt(y: u128) -> u128 {
-    if y == 0 {
+/// Amount of `total` unlocked at `now` on a schedule starting at `start`: nothing until
+/// `cliff` seconds have passed, then linearly over the following `unlock` seconds
+pub fn vested_amount(total: u64, start: i64, cliff: i64, unlock: i64, now: i64) -> u64 {
+    let elapsed = now.saturating_sub(start).saturating_sub(cliff);
+    if elapsed < 0 {
         return 0;
     }
-
-    let mut z = y;
-    let mut x = y / 2 + 1;
-
-    while x < z {
-        z = x;
-        x = (y / x + x) / 2;
+    if elapsed >= unlock {
+        return total;
     }
 
-    z
+    (total as u128 * elapsed as u128 / unlock as u128) as u64
 }
 
-/// Calculate natural logarithm approximation
-pub fn ln_approx(x: u128) -> u128 {
-    if x <= 1 {
-        return 0;
-    }
-
-    // Simple approximation: ln(x) ≈ (x - 1) for values close to 1
-    // For larger values, use iterative approach
-    let mut result = 0u128;
-    let mut temp = x;
-
-    while temp > 2 {
-        temp /= 2;
-        result += 693147; // ln(2) * 1e6
-    }
-
-    result
+/// Merkle leaf for an allowlisted wallet and its token allocation (0 for uncapped)
+pub fn allowlist_leaf(wallet: &Pubkey, allocation: u64) -> [u8; 32] {
+    hashv(&[wallet.as_ref(), &allocation.to_le_bytes()]).to_bytes()
 }
 
-/// Calculate exponential approximation
-pub fn exp_approx(x: u128) -> u128 {
-    if x == 0 {
-        return 1_000_000;
-    }
-
-    // Using Taylor series: e^x = 1 + x + x^2/2! + x^3/3! + ...
-    // Simplified for small values
-    let x_scaled = x as u128;
-    let term1 = 1_000_000u128;
-    let term2 = x_scaled;
-    let term3 = x_scaled.saturating_mul(x_scaled) / 2_000_000;
-    let term4 = x_scaled.saturating_mul(x_scaled).saturating_mul(x_scaled) / 6_000_000_000_000;
-
-    term1
-        .saturating_add(term2)
-        .saturating_add(term3)
-        .saturating_add(term4)
+/// Verify a keccak merkle proof, hashing each pair of nodes in sorted order
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            hashv(&[&node, sibling]).to_bytes()
+        } else {
+            hashv(&[sibling, &node]).to_bytes()
+        }
+    });
+
+    computed == *root
 }
 
 #[cfg(test)]
@@ -63,25 +40,39 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_sqrt() {
-        assert_eq!(sqrt(0), 0);
-        assert_eq!(sqrt(1), 1);
-        assert_eq!(sqrt(4), 2);
-        assert_eq!(sqrt(9), 3);
-        assert_eq!(sqrt(16), 4);
-        assert_eq!(sqrt(100), 10);
-    }
-
-    #[test]
-    fn test_ln_approx() {
-        assert_eq!(ln_approx(1), 0);
-        assert!(ln_approx(2) > 0);
-        assert!(ln_approx(10) > ln_approx(2));
+    fn test_verify_merkle_proof() {
+        let wallets: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let leaves: Vec<[u8; 32]> = wallets.iter().map(|wallet| allowlist_leaf(wallet, 1_000)).collect();
+
+        let pair = |a: [u8; 32], b: [u8; 32]| {
+            if a <= b { hashv(&[&a, &b]).to_bytes() } else { hashv(&[&b, &a]).to_bytes() }
+        };
+        let left = pair(leaves[0], leaves[1]);
+        let root = pair(left, leaves[2]);
+
+        assert!(verify_merkle_proof(&[leaves[1], leaves[2]], &root, leaves[0]));
+        assert!(verify_merkle_proof(&[leaves[0], leaves[2]], &root, leaves[1]));
+        assert!(verify_merkle_proof(&[left], &root, leaves[2]));
+
+        // Wrong allocation, wrong wallet or a truncated proof all fail
+        assert!(!verify_merkle_proof(&[leaves[1], leaves[2]], &root, allowlist_leaf(&wallets[0], 2_000)));
+        assert!(!verify_merkle_proof(&[leaves[1], leaves[2]], &root, allowlist_leaf(&Pubkey::new_unique(), 1_000)));
+        assert!(!verify_merkle_proof(&[leaves[1]], &root, leaves[0]));
     }
 
     #[test]
-    fn test_exp_approx() {
-        assert_eq!(exp_approx(0), 1_000_000);
-        assert!(exp_approx(1_000_000) > 1_000_000);
+    fn test_vested_amount() {
+        // 1 day cliff then 10 days linear
+        let (start, cliff, unlock) = (1_000, 86_400, 864_000);
+        assert_eq!(vested_amount(1_000_000, start, cliff, unlock, 0), 0);
+        assert_eq!(vested_amount(1_000_000, start, cliff, unlock, start + cliff - 1), 0);
+        assert_eq!(vested_amount(1_000_000, start, cliff, unlock, start + cliff), 0);
+        assert_eq!(vested_amount(1_000_000, start, cliff, unlock, start + cliff + unlock / 4), 250_000);
+        assert_eq!(vested_amount(1_000_000, start, cliff, unlock, start + cliff + unlock), 1_000_000);
+        assert_eq!(vested_amount(1_000_000, start, cliff, unlock, i64::MAX), 1_000_000);
+
+        // No unlock period releases everything at the cliff
+        assert_eq!(vested_amount(1_000_000, start, cliff, 0, start + cliff - 1), 0);
+        assert_eq!(vested_amount(1_000_000, start, cliff, 0, start + cliff), 1_000_000);
     }
 }
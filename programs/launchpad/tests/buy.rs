@@ -0,0 +1,255 @@
+//! Buying from the curve through the program.
+//!
+//!     cargo test -p launchpad --test buy
+
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use common::*;
+use launchpad::errors::LaunchpadError;
+use launchpad::fixed_point::{mul_div, Rounding};
+use launchpad::state::{CreateLaunchParams, Launch, LaunchStatus};
+use solana_sdk::clock::Clock;
+use solana_sdk::signature::Signer;
+
+#[tokio::test]
+async fn test_final_buy_partially_fills() {
+    for token_program in TOKEN_PROGRAMS {
+        let mut context = program_test().start_with_context().await;
+        let creator = context.payer.insecure_clone();
+
+        let community_pool = initialize(&mut context).await;
+        let (mint, launch) = create_launch(&mut context, &creator, token_program, None).await;
+
+        // Twice what the whole curve costs, so the buy runs past the end
+        let launch_state = get_launch(&mut context, &launch).await;
+        let remaining_cost = launch_state.calculate_buy_cost(SELL_AMOUNT, &[]).unwrap();
+        let sol_amount = remaining_cost * 2;
+
+        let buyer = wallet(&mut context, sol_amount + LAMPORTS_PER_SOL).await;
+        let buyer_before = balance(&mut context, &buyer.pubkey()).await;
+        let pool_before = balance(&mut context, &community_pool).await;
+
+        buy(&mut context, &buyer, &mint, &launch, &community_pool, token_program, sol_amount).await.unwrap();
+
+        let launch_state = get_launch(&mut context, &launch).await;
+        assert_eq!(launch_state.tokens_sold, launch_state.total_sell_amount);
+        assert_eq!(launch_state.sol_raised, remaining_cost);
+        assert!(launch_state.status == LaunchStatus::Completed);
+
+        // The buyer paid for the accounts it opened and the filled tokens, nothing more
+        let position = position_address(&launch, &buyer.pubkey());
+        let buyer_token_account = get_associated_token_address_with_program_id(&buyer.pubkey(), &mint, &token_program);
+        let rent = balance(&mut context, &position).await + balance(&mut context, &buyer_token_account).await;
+        let charged = buyer_before - balance(&mut context, &buyer.pubkey()).await - rent;
+
+        let fee = charged - remaining_cost;
+        assert!(fee as u128 * 10000 >= charged as u128 * FEE_BPS as u128);
+        assert_eq!(fee, launch_state.creator_fee_earned + balance(&mut context, &community_pool).await - pool_before);
+        assert!(charged < sol_amount);
+
+        let position = get_position(&mut context, &launch, &buyer.pubkey()).await;
+        assert_eq!(position.tokens_bought, SELL_AMOUNT);
+        assert_eq!(position.sol_spent, charged);
+        assert_eq!(position.sol_in_curve, remaining_cost);
+    }
+}
+
+#[tokio::test]
+async fn test_purchase_caps() {
+    for token_program in TOKEN_PROGRAMS {
+        let mut context = program_test().start_with_context().await;
+        let creator = context.payer.insecure_clone();
+
+        let community_pool = initialize(&mut context).await;
+        let whale = wallet(&mut context, 100 * LAMPORTS_PER_SOL).await;
+        let other = wallet(&mut context, 100 * LAMPORTS_PER_SOL).await;
+        let (mint, launch) = create_launch(&mut context, &creator, token_program, None).await;
+
+        // Caps worth 2 SOL a buy and 4 SOL a wallet at the start of the curve, for 10 minutes
+        let launch_state = get_launch(&mut context, &launch).await;
+        let max_per_tx = launch_state.calculate_tokens_for_sol(2 * LAMPORTS_PER_SOL, &[]).unwrap();
+        let max_per_wallet = launch_state.calculate_tokens_for_sol(4 * LAMPORTS_PER_SOL, &[]).unwrap();
+        configure_purchase_caps(&mut context, &creator, &launch, max_per_tx, max_per_wallet, 600).await.unwrap();
+
+        assert_error(
+            buy(&mut context, &whale, &mint, &launch, &community_pool, token_program, 3 * LAMPORTS_PER_SOL).await,
+            LaunchpadError::PurchaseCapExceeded,
+        );
+
+        // Buys under the per-tx cap add up to the per-wallet cap
+        let half_sol = 3 * LAMPORTS_PER_SOL / 2;
+        buy(&mut context, &whale, &mint, &launch, &community_pool, token_program, half_sol).await.unwrap();
+        buy(&mut context, &whale, &mint, &launch, &community_pool, token_program, half_sol).await.unwrap();
+        assert_error(
+            buy(&mut context, &whale, &mint, &launch, &community_pool, token_program, half_sol).await,
+            LaunchpadError::PurchaseCapExceeded,
+        );
+
+        // Other wallets have their own allowance, and selling frees up the whale's
+        buy(&mut context, &other, &mint, &launch, &community_pool, token_program, half_sol).await.unwrap();
+        let held = token_balance(&mut context, &whale.pubkey(), &mint, token_program).await;
+        sell(&mut context, &whale, &mint, &launch, token_program, held / 2).await.unwrap();
+        buy(&mut context, &whale, &mint, &launch, &community_pool, token_program, half_sol).await.unwrap();
+        assert!(get_position(&mut context, &launch, &whale.pubkey()).await.net_tokens() <= max_per_wallet);
+
+        // The caps lift once their window is over
+        let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+        context.set_sysvar(&Clock { unix_timestamp: launch_state.launch_time + 600, ..clock });
+        buy(&mut context, &whale, &mint, &launch, &community_pool, token_program, 10 * LAMPORTS_PER_SOL).await.unwrap();
+        assert!(get_position(&mut context, &launch, &whale.pubkey()).await.net_tokens() > max_per_wallet);
+    }
+}
+
+#[tokio::test]
+async fn test_launch_fee_needs_decay_window() {
+    for token_program in TOKEN_PROGRAMS {
+        let mut context = program_test().start_with_context().await;
+        let creator = context.payer.insecure_clone();
+
+        let community_pool = initialize(&mut context).await;
+        let buyer = wallet(&mut context, 100 * LAMPORTS_PER_SOL).await;
+        let (mint, launch) = create_launch(&mut context, &creator, token_program, None).await;
+
+        // A fee above the base with no window would never apply
+        assert_error(
+            configure_launch_fee(&mut context, &creator, &launch, 5_000, 0).await,
+            LaunchpadError::InvalidLaunchFee,
+        );
+        configure_launch_fee(&mut context, &creator, &launch, FEE_BPS, 0).await.unwrap();
+
+        // Nor may it decay so slowly that it becomes a lasting fee
+        assert_error(
+            configure_launch_fee(&mut context, &creator, &launch, 5_000, Launch::MAX_LAUNCH_FEE_DURATION + 1).await,
+            LaunchpadError::InvalidLaunchFee,
+        );
+        assert_error(
+            configure_launch_fee(&mut context, &creator, &launch, Launch::MAX_LAUNCH_FEE_BPS, i64::MAX).await,
+            LaunchpadError::InvalidLaunchFee,
+        );
+        configure_launch_fee(&mut context, &creator, &launch, 5_000, 600).await.unwrap();
+
+        // The first buy pays close to the full launch fee
+        let pool_before = balance(&mut context, &community_pool).await;
+        buy(&mut context, &buyer, &mint, &launch, &community_pool, token_program, 10 * LAMPORTS_PER_SOL).await.unwrap();
+        let launch_state = get_launch(&mut context, &launch).await;
+        let fee = launch_state.creator_fee_earned + balance(&mut context, &community_pool).await - pool_before;
+        assert_eq!(fee + launch_state.sol_raised, 10 * LAMPORTS_PER_SOL);
+        assert!(fee > 4 * LAMPORTS_PER_SOL);
+    }
+}
+
+#[tokio::test]
+async fn test_buy_exact_out() {
+    for token_program in TOKEN_PROGRAMS {
+        let mut context = program_test().start_with_context().await;
+        let creator = context.payer.insecure_clone();
+
+        let community_pool = initialize(&mut context).await;
+        let buyer = wallet(&mut context, 1_000 * LAMPORTS_PER_SOL).await;
+        let (mint, launch) = create_launch(&mut context, &creator, token_program, None).await;
+
+        // The curve cost of the tokens grossed up so the fee is FEE_BPS of what is paid
+        let token_amount = SELL_AMOUNT / 100;
+        let net_sol = get_launch(&mut context, &launch).await.calculate_buy_cost(token_amount, &[]).unwrap();
+        let charged = mul_div(net_sol as u128, 10_000, 10_000 - FEE_BPS as u128, Rounding::Up).unwrap() as u64;
+        let fee = charged - net_sol;
+        assert!(fee > 0);
+
+        assert_error(
+            buy_exact_out(&mut context, &buyer, &mint, &launch, token_program, token_amount, charged - 1).await,
+            LaunchpadError::SlippageExceeded,
+        );
+
+        let buyer_before = balance(&mut context, &buyer.pubkey()).await;
+        let pool_before = balance(&mut context, &community_pool).await;
+        buy_exact_out(&mut context, &buyer, &mint, &launch, token_program, token_amount, charged)
+            .await
+            .unwrap();
+
+        // Exactly token_amount delivered for exactly the grossed-up cost, the fee split between
+        // the creator and the community pool
+        assert_eq!(token_balance(&mut context, &buyer.pubkey(), &mint, token_program).await, token_amount);
+        let position = position_address(&launch, &buyer.pubkey());
+        let buyer_token_account = get_associated_token_address_with_program_id(&buyer.pubkey(), &mint, &token_program);
+        let rent = balance(&mut context, &position).await + balance(&mut context, &buyer_token_account).await;
+        assert_eq!(buyer_before - balance(&mut context, &buyer.pubkey()).await - rent, charged);
+
+        let launch_state = get_launch(&mut context, &launch).await;
+        assert_eq!(launch_state.tokens_sold, token_amount);
+        assert_eq!(launch_state.sol_raised, net_sol);
+        assert_eq!(launch_state.creator_fee_earned, fee / 2);
+        assert_eq!(balance(&mut context, &community_pool).await - pool_before, fee - fee / 2);
+
+        let position = get_position(&mut context, &launch, &buyer.pubkey()).await;
+        assert_eq!(position.tokens_bought, token_amount);
+        assert_eq!(position.sol_spent, charged);
+        assert_eq!(position.sol_in_curve, net_sol);
+
+        // One token more than the curve has left is rejected, exactly what is left sells it out
+        let remaining = SELL_AMOUNT - launch_state.tokens_sold;
+        let remaining_cost = launch_state.calculate_buy_cost(remaining, &[]).unwrap();
+        assert_error(
+            buy_exact_out(&mut context, &buyer, &mint, &launch, token_program, remaining + 1, u64::MAX).await,
+            LaunchpadError::InsufficientTokens,
+        );
+        buy_exact_out(&mut context, &buyer, &mint, &launch, token_program, remaining, u64::MAX)
+            .await
+            .unwrap();
+
+        let completed = get_launch(&mut context, &launch).await;
+        assert_eq!(completed.tokens_sold, SELL_AMOUNT);
+        assert_eq!(completed.sol_raised, net_sol + remaining_cost);
+        assert!(completed.status == LaunchStatus::Completed);
+        assert_eq!(token_balance(&mut context, &buyer.pubkey(), &mint, token_program).await, SELL_AMOUNT);
+
+        assert_error(
+            buy_exact_out(&mut context, &buyer, &mint, &launch, token_program, 1, u64::MAX).await,
+            LaunchpadError::LaunchNotActive,
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_start_time_opens_trading() {
+    for token_program in TOKEN_PROGRAMS {
+        let mut context = program_test().start_with_context().await;
+        let creator = context.payer.insecure_clone();
+
+        let community_pool = initialize(&mut context).await;
+        let buyer = wallet(&mut context, 10 * LAMPORTS_PER_SOL).await;
+        let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+
+        // A start already behind the clock is rejected
+        let past = CreateLaunchParams { start_time: Some(clock.unix_timestamp - 1), ..launch_params(None) };
+        assert_error(
+            try_create_launch_with(&mut context, &creator, token_program, past).await.map(|_| ()),
+            LaunchpadError::InvalidStartTime,
+        );
+
+        let start_time = clock.unix_timestamp + 3_600;
+        let params = CreateLaunchParams { start_time: Some(start_time), ..launch_params(None) };
+        let (mint, launch) = create_launch_with(&mut context, &creator, token_program, params).await;
+        assert_eq!(get_launch(&mut context, &launch).await.launch_time, start_time);
+
+        assert_error(
+            buy(&mut context, &buyer, &mint, &launch, &community_pool, token_program, LAMPORTS_PER_SOL).await,
+            LaunchpadError::LaunchNotStarted,
+        );
+
+        context.set_sysvar(&Clock { unix_timestamp: start_time, ..clock.clone() });
+        buy(&mut context, &buyer, &mint, &launch, &community_pool, token_program, LAMPORTS_PER_SOL).await.unwrap();
+        let bought = token_balance(&mut context, &buyer.pubkey(), &mint, token_program).await;
+
+        // Selling needs tokens, so wind the clock back before the start to check it's shut too
+        context.set_sysvar(&Clock { unix_timestamp: start_time - 1, ..clock.clone() });
+        assert_error(
+            sell(&mut context, &buyer, &mint, &launch, token_program, bought).await,
+            LaunchpadError::LaunchNotStarted,
+        );
+
+        context.set_sysvar(&Clock { unix_timestamp: start_time + 1, ..clock });
+        sell(&mut context, &buyer, &mint, &launch, token_program, bought).await.unwrap();
+        assert_eq!(get_launch(&mut context, &launch).await.tokens_sold, 0);
+    }
+}
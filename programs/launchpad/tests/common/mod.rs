@@ -0,0 +1,949 @@
+//! Shared setup for the program-test suites. The program runs natively, or from
+//! `target/deploy/launchpad.so` after `anchor build` with `BPF_OUT_DIR=target/deploy`.
+
+#![allow(dead_code)]
+
+mod token_metadata;
+
+use anchor_lang::solana_program::account_info::AccountInfo;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::metadata::mpl_token_metadata;
+use anchor_spl::token_2022::spl_token_2022::extension::StateWithExtensions;
+use anchor_spl::token_2022::spl_token_2022::state::{Account as TokenAccount, Mint};
+use launchpad::state::{
+    CreateLaunchParams, CurvePoint, CurveType, Launch, LaunchLimits, LaunchpadConfig, MigrateType, PresaleProof,
+    UserPosition,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::{AccountMeta, Instruction, InstructionError};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use solana_sdk::{system_instruction, system_program, sysvar};
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use std::fmt::Display;
+use std::path::Path;
+
+pub const NAME: &str = "Test Token";
+pub const SYMBOL: &str = "TEST";
+pub const URI: &str = "https://test.com/metadata.json";
+
+pub const FEE_BPS: u16 = 100;
+pub const SUPPLY: u64 = 1_000_000_000_000_000_000;
+pub const SELL_AMOUNT: u64 = 520_000_000_000_000_000;
+pub const FUND_RAISING: u64 = 85_000_000_000;
+
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+/// Every case that creates a launch runs under both token programs
+pub const TOKEN_PROGRAMS: [Pubkey; 2] = [anchor_spl::token::ID, anchor_spl::token_2022::ID];
+
+/// Token Metadata program dumped by `scripts/fetch-fixtures.sh`
+pub const METADATA_FIXTURE: &str = "tests/fixtures/mpl_token_metadata.so";
+
+pub fn has_metadata_fixture() -> bool {
+    Path::new(METADATA_FIXTURE).exists()
+}
+
+/// The launchpad with Token Metadata for SPL Token launches: the real program once it's been
+/// fetched, a stand-in for the instructions launches use otherwise
+pub fn program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::new("launchpad", launchpad::ID, processor!(process_instruction));
+    if has_metadata_fixture() {
+        program_test.add_program("mpl_token_metadata", mpl_token_metadata::ID, None);
+    } else {
+        // Native even when the launchpad itself runs from BPF_OUT_DIR
+        program_test.prefer_bpf(false);
+        program_test.add_program(
+            "mpl_token_metadata",
+            mpl_token_metadata::ID,
+            processor!(token_metadata::process_instruction),
+        );
+    }
+    program_test
+}
+
+// Anchor's entrypoint ties the account infos to their own lifetime, which the native
+// processor signature can't, so hand it a copy that lives for the rest of the test
+fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    launchpad::entry(program_id, accounts, data)
+}
+
+pub fn config_address() -> Pubkey {
+    Pubkey::find_program_address(&[b"config"], &launchpad::ID).0
+}
+
+pub fn piecewise_curve_address(launch: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"curve", launch.as_ref()], &launchpad::ID).0
+}
+
+pub fn allocation_table_address(launch: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"allocations", launch.as_ref()], &launchpad::ID).0
+}
+
+pub fn vesting_vault_address(launch: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"vesting", launch.as_ref()], &launchpad::ID).0
+}
+
+pub fn allocation_vault_address(launch: &Pubkey, index: u8) -> Pubkey {
+    Pubkey::find_program_address(&[b"vesting", launch.as_ref(), &[index]], &launchpad::ID).0
+}
+
+pub fn position_address(launch: &Pubkey, user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"position", launch.as_ref(), user.as_ref()], &launchpad::ID).0
+}
+
+/// Send `instructions` paid for by the context payer and signed by `signers`
+pub async fn process(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let payer = context.payer.insecure_clone();
+    let mut all_signers = vec![&payer];
+    all_signers.extend_from_slice(signers);
+
+    // A fresh blockhash keeps repeated identical instructions from being deduplicated
+    let recent_blockhash = context.get_new_latest_blockhash().await?;
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &all_signers,
+        recent_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+/// Whether the launchpad runs from `launchpad.so` in `BPF_OUT_DIR`, the only way compute
+/// units get metered
+pub fn runs_bpf() -> bool {
+    std::env::var("BPF_OUT_DIR").is_ok_and(|dir| Path::new(&dir).join("launchpad.so").exists())
+}
+
+/// Simulate `instructions` like `process` does and return the compute units they consume
+pub async fn compute_units(context: &mut ProgramTestContext, instructions: &[Instruction], signers: &[&Keypair]) -> u64 {
+    let payer = context.payer.insecure_clone();
+    let mut all_signers = vec![&payer];
+    all_signers.extend_from_slice(signers);
+
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &all_signers,
+        recent_blockhash,
+    );
+    let simulation = context.banks_client.simulate_transaction(transaction).await.unwrap();
+    simulation.result.expect("simulated transaction").unwrap();
+    simulation.simulation_details.expect("simulation details").units_consumed
+}
+
+/// Transfer `lamports` from the context payer to `to`
+pub async fn fund(context: &mut ProgramTestContext, to: &Pubkey, lamports: u64) {
+    let transfer = system_instruction::transfer(&context.payer.pubkey(), to, lamports);
+    process(context, &[transfer], &[]).await.unwrap();
+}
+
+/// A new wallet holding `lamports`
+pub async fn wallet(context: &mut ProgramTestContext, lamports: u64) -> Keypair {
+    let wallet = Keypair::new();
+    fund(context, &wallet.pubkey(), lamports).await;
+    wallet
+}
+
+pub async fn balance(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    context.banks_client.get_balance(*address).await.unwrap()
+}
+
+/// Token balance of `owner`'s associated token account for `mint`
+pub async fn token_balance(context: &mut ProgramTestContext, owner: &Pubkey, mint: &Pubkey, token_program: Pubkey) -> u64 {
+    let address = get_associated_token_address_with_program_id(owner, mint, &token_program);
+    token_account_balance(context, &address).await
+}
+
+pub async fn token_account_balance(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    let account = context.banks_client.get_account(*address).await.unwrap().expect("token account");
+    StateWithExtensions::<TokenAccount>::unpack(&account.data).unwrap().base.amount
+}
+
+pub async fn mint_supply(context: &mut ProgramTestContext, mint: &Pubkey) -> u64 {
+    let account = context.banks_client.get_account(*mint).await.unwrap().expect("mint account");
+    StateWithExtensions::<Mint>::unpack(&account.data).unwrap().base.supply
+}
+
+/// Assert that `result` failed with `error`, one of the program's or an Anchor constraint's
+pub fn assert_error(result: Result<(), BanksClientError>, error: impl Into<u32> + Display) {
+    let expected = error.to_string();
+    match result.unwrap_err().unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            assert_eq!(code, error.into(), "expected {}", expected)
+        }
+        other => panic!("expected {}, got {:?}", expected, other),
+    }
+}
+
+pub async fn get_config(context: &mut ProgramTestContext) -> LaunchpadConfig {
+    let account = context.banks_client.get_account(config_address()).await.unwrap().expect("config account");
+    LaunchpadConfig::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+pub async fn get_launch(context: &mut ProgramTestContext, launch: &Pubkey) -> Launch {
+    let account = context.banks_client.get_account(*launch).await.unwrap().expect("launch account");
+    Launch::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+pub async fn get_position(context: &mut ProgramTestContext, launch: &Pubkey, user: &Pubkey) -> UserPosition {
+    let address = position_address(launch, user);
+    let account = context.banks_client.get_account(address).await.unwrap().expect("position account");
+    UserPosition::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+/// The breakpoints account of `launch` if it is a Piecewise launch
+pub async fn piecewise_curve(context: &mut ProgramTestContext, launch: &Pubkey) -> Option<Pubkey> {
+    let address = piecewise_curve_address(launch);
+    context.banks_client.get_account(address).await.unwrap().map(|_| address)
+}
+
+/// Initialize the launchpad with the context payer as config authority, returning the
+/// community pool
+pub async fn initialize(context: &mut ProgramTestContext) -> Pubkey {
+    let community_pool = Keypair::new().pubkey();
+
+    let initialize = Instruction {
+        program_id: launchpad::ID,
+        accounts: launchpad::accounts::Initialize {
+            config: config_address(),
+            authority: context.payer.pubkey(),
+            community_pool,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: launchpad::instruction::Initialize {
+            fee_percentage: FEE_BPS,
+            referral_fee: 10,
+        }
+        .data(),
+    };
+    process(context, &[initialize], &[]).await.unwrap();
+
+    // Rent exempt up front so small fee transfers land
+    fund(context, &community_pool, LAMPORTS_PER_SOL).await;
+
+    community_pool
+}
+
+/// Update the config's fees and community pool, signed by `authority`
+pub async fn update_config(
+    context: &mut ProgramTestContext,
+    authority: &Keypair,
+    fee_percentage: Option<u16>,
+    community_pool: Option<Pubkey>,
+    referral_fee: Option<u16>,
+) -> Result<(), BanksClientError> {
+    let update = Instruction {
+        program_id: launchpad::ID,
+        accounts: launchpad::accounts::UpdateConfig {
+            config: config_address(),
+            authority: authority.pubkey(),
+        }
+        .to_account_metas(None),
+        data: launchpad::instruction::UpdateConfig {
+            fee_percentage,
+            community_pool,
+            referral_fee,
+        }
+        .data(),
+    };
+
+    process(context, &[update], &[authority]).await
+}
+
+/// Propose `new_authority` for the config, signed by `authority`
+pub async fn propose_authority(
+    context: &mut ProgramTestContext,
+    authority: &Keypair,
+    new_authority: &Pubkey,
+) -> Result<(), BanksClientError> {
+    let propose = Instruction {
+        program_id: launchpad::ID,
+        accounts: launchpad::accounts::UpdateConfig {
+            config: config_address(),
+            authority: authority.pubkey(),
+        }
+        .to_account_metas(None),
+        data: launchpad::instruction::ProposeAuthority { new_authority: *new_authority }.data(),
+    };
+
+    process(context, &[propose], &[authority]).await
+}
+
+/// Accept the config authority as `new_authority`
+pub async fn accept_authority(context: &mut ProgramTestContext, new_authority: &Keypair) -> Result<(), BanksClientError> {
+    let accept = Instruction {
+        program_id: launchpad::ID,
+        accounts: launchpad::accounts::AcceptAuthority {
+            config: config_address(),
+            new_authority: new_authority.pubkey(),
+        }
+        .to_account_metas(None),
+        data: launchpad::instruction::AcceptAuthority {}.data(),
+    };
+
+    process(context, &[accept], &[new_authority]).await
+}
+
+/// Terms of a Linear launch selling 52% of supply for `FUND_RAISING`, with no vesting
+/// schedule or allocations
+pub fn launch_params(deadline: Option<i64>) -> CreateLaunchParams {
+    CreateLaunchParams {
+        name: NAME.to_string(),
+        symbol: SYMBOL.to_string(),
+        uri: URI.to_string(),
+        decimals: LaunchLimits::DEFAULT.min_decimals,
+        supply: SUPPLY,
+        total_sell_amount: SELL_AMOUNT,
+        total_fund_raising: FUND_RAISING,
+        curve_type: CurveType::Linear,
+        curve_points: vec![],
+        migrate_type: MigrateType::CPMM,
+        cliff_period: 0,
+        unlock_period: 0,
+        start_time: None,
+        deadline,
+        allocations: vec![],
+    }
+}
+
+/// Create a Linear launch by `creator` under `token_program`, returning the mint and launch
+/// addresses
+pub async fn create_launch(
+    context: &mut ProgramTestContext,
+    creator: &Keypair,
+    token_program: Pubkey,
+    deadline: Option<i64>,
+) -> (Pubkey, Pubkey) {
+    create_launch_with(context, creator, token_program, launch_params(deadline)).await
+}
+
+/// Create a launch on `params` by `creator` under `token_program`, returning the mint and
+/// launch addresses
+pub async fn create_launch_with(
+    context: &mut ProgramTestContext,
+    creator: &Keypair,
+    token_program: Pubkey,
+    params: CreateLaunchParams,
+) -> (Pubkey, Pubkey) {
+    try_create_launch_with(context, creator, token_program, params).await.unwrap()
+}
+
+/// Create a Piecewise launch through `curve_points` by `creator` under `token_program`,
+/// returning the mint and launch addresses
+pub async fn create_piecewise_launch(
+    context: &mut ProgramTestContext,
+    creator: &Keypair,
+    token_program: Pubkey,
+    curve_points: Vec<CurvePoint>,
+) -> Result<(Pubkey, Pubkey), BanksClientError> {
+    let params = CreateLaunchParams {
+        curve_type: CurveType::Piecewise,
+        curve_points,
+        ..launch_params(None)
+    };
+    try_create_launch_with(context, creator, token_program, params).await
+}
+
+/// Metaplex metadata account and program of `mint`, only SPL Token mints have them
+pub fn metadata_accounts(mint: &Pubkey, token_program: Pubkey) -> (Option<Pubkey>, Option<Pubkey>) {
+    if token_program == anchor_spl::token::ID {
+        let (metadata, _) = mpl_token_metadata::accounts::Metadata::find_pda(mint);
+        (Some(metadata), Some(mpl_token_metadata::ID))
+    } else {
+        (None, None)
+    }
+}
+
+/// Try to create a launch on `params` by `creator` under `token_program`, returning the mint
+/// and launch addresses
+pub async fn try_create_launch_with(
+    context: &mut ProgramTestContext,
+    creator: &Keypair,
+    token_program: Pubkey,
+    params: CreateLaunchParams,
+) -> Result<(Pubkey, Pubkey), BanksClientError> {
+    let mint_keypair = Keypair::new();
+    let mint = mint_keypair.pubkey();
+    let (launch, _) = Pubkey::find_program_address(&[b"launch", mint.as_ref()], &launchpad::ID);
+    let vesting_vault = vesting_vault_address(&launch);
+    let piecewise_curve = (params.curve_type == CurveType::Piecewise).then(|| piecewise_curve_address(&launch));
+    let allocation_table = (!params.allocations.is_empty()).then(|| allocation_table_address(&launch));
+    let allocation_vaults = (0..params.allocations.len() as u8)
+        .map(|index| AccountMeta::new(allocation_vault_address(&launch, index), false));
+
+    let (metadata, token_metadata_program) = metadata_accounts(&mint, token_program);
+
+    let mut create_launch = Instruction {
+        program_id: launchpad::ID,
+        accounts: launchpad::accounts::CreateLaunch {
+            launch,
+            mint,
+            launch_token_account: get_associated_token_address_with_program_id(&launch, &mint, &token_program),
+            vesting_vault,
+            piecewise_curve,
+            allocation_table,
+            config: config_address(),
+            creator: creator.pubkey(),
+            metadata,
+            token_metadata_program,
+            token_program,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        }
+        .to_account_metas(None),
+        data: launchpad::instruction::CreateLaunch { params }.data(),
+    };
+    create_launch.accounts.extend(allocation_vaults);
+
+    process(
+        context,
+        &[ComputeBudgetInstruction::set_compute_unit_limit(400_000), create_launch],
+        &[creator, &mint_keypair],
+    )
+    .await?;
+
+    Ok((mint, launch))
+}
+
+/// Restrict buys on `launch` to the `merkle_root` allowlist until `end_time`
+pub async fn configure_presale(
+    context: &mut ProgramTestContext,
+    creator: &Keypair,
+    launch: &Pubkey,
+    merkle_root: [u8; 32],
+    end_time: i64,
+) -> Result<(), BanksClientError> {
+    let configure = Instruction {
+        program_id: launchpad::ID,
+        accounts: launchpad::accounts::ConfigurePresale {
+            launch: *launch,
+            creator: creator.pubkey(),
+        }
+        .to_account_metas(None),
+        data: launchpad::instruction::ConfigurePresale { merkle_root, end_time }.data(),
+    };
+
+    process(context, &[configure], &[creator]).await
+}
+
+/// Cap buys on `launch` per transaction and per wallet for `duration` seconds after launch
+pub async fn configure_purchase_caps(
+    context: &mut ProgramTestContext,
+    creator: &Keypair,
+    launch: &Pubkey,
+    max_tokens_per_tx: u64,
+    max_tokens_per_wallet: u64,
+    duration: i64,
+) -> Result<(), BanksClientError> {
+    let configure = Instruction {
+        program_id: launchpad::ID,
+        accounts: launchpad::accounts::ConfigureAntiSnipe {
+            launch: *launch,
+            config: config_address(),
+            creator: creator.pubkey(),
+        }
+        .to_account_metas(None),
+        data: launchpad::instruction::ConfigurePurchaseCaps {
+            max_tokens_per_tx,
+            max_tokens_per_wallet,
+            duration,
+        }
+        .data(),
+    };
+
+    process(context, &[configure], &[creator]).await
+}
+
+/// Start buys on `launch` at `launch_fee_bps`, decaying to the base fee over `duration` seconds
+pub async fn configure_launch_fee(
+    context: &mut ProgramTestContext,
+    creator: &Keypair,
+    launch: &Pubkey,
+    launch_fee_bps: u16,
+    duration: i64,
+) -> Result<(), BanksClientError> {
+    let configure = Instruction {
+        program_id: launchpad::ID,
+        accounts: launchpad::accounts::ConfigureAntiSnipe {
+            launch: *launch,
+            config: config_address(),
+            creator: creator.pubkey(),
+        }
+        .to_account_metas(None),
+        data: launchpad::instruction::ConfigureLaunchFee { launch_fee_bps, duration }.data(),
+    };
+
+    process(context, &[configure], &[creator]).await
+}
+
+/// Buy from the curve with `sol_amount`, fees included
+pub async fn buy(
+    context: &mut ProgramTestContext,
+    buyer: &Keypair,
+    mint: &Pubkey,
+    launch: &Pubkey,
+    community_pool: &Pubkey,
+    token_program: Pubkey,
+    sol_amount: u64,
+) -> Result<(), BanksClientError> {
+    let piecewise_curve = piecewise_curve(context, launch).await;
+    let data = launchpad::instruction::BuyTokens {
+        sol_amount,
+        min_tokens_out: 0,
+        presale_proof: None,
+    }
+    .data();
+    let buy = buy_tokens_instruction(buyer, mint, launch, community_pool, token_program, piecewise_curve, data);
+    process(context, &[buy], &[buyer]).await
+}
+
+/// Instruction buying from a non-Piecewise curve with `sol_amount`, fees included, and an
+/// optional presale allowlist proof
+pub fn buy_instruction(
+    buyer: &Keypair,
+    mint: &Pubkey,
+    launch: &Pubkey,
+    community_pool: &Pubkey,
+    token_program: Pubkey,
+    sol_amount: u64,
+    presale_proof: Option<PresaleProof>,
+) -> Instruction {
+    let data = launchpad::instruction::BuyTokens {
+        sol_amount,
+        min_tokens_out: 0,
+        presale_proof,
+    }
+    .data();
+    buy_tokens_instruction(buyer, mint, launch, community_pool, token_program, None, data)
+}
+
+/// Instruction buying from a Piecewise curve with `sol_amount`, fees included
+pub fn piecewise_buy_instruction(
+    buyer: &Keypair,
+    mint: &Pubkey,
+    launch: &Pubkey,
+    community_pool: &Pubkey,
+    token_program: Pubkey,
+    sol_amount: u64,
+) -> Instruction {
+    let data = launchpad::instruction::BuyTokens {
+        sol_amount,
+        min_tokens_out: 0,
+        presale_proof: None,
+    }
+    .data();
+    let piecewise_curve = Some(piecewise_curve_address(launch));
+    buy_tokens_instruction(buyer, mint, launch, community_pool, token_program, piecewise_curve, data)
+}
+
+fn buy_tokens_instruction(
+    buyer: &Keypair,
+    mint: &Pubkey,
+    launch: &Pubkey,
+    community_pool: &Pubkey,
+    token_program: Pubkey,
+    piecewise_curve: Option<Pubkey>,
+    data: Vec<u8>,
+) -> Instruction {
+    Instruction {
+        program_id: launchpad::ID,
+        accounts: launchpad::accounts::BuyTokens {
+            launch: *launch,
+            mint: *mint,
+            launch_token_account: get_associated_token_address_with_program_id(launch, mint, &token_program),
+            piecewise_curve,
+            user_position: position_address(launch, &buyer.pubkey()),
+            buyer_token_account: get_associated_token_address_with_program_id(&buyer.pubkey(), mint, &token_program),
+            buyer: buyer.pubkey(),
+            config: config_address(),
+            community_pool: *community_pool,
+            token_program,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        }
+        .to_account_metas(None),
+        data,
+    }
+}
+
+/// Buy exactly `token_amount` from the curve, paying at most `max_sol_in` with fees to the
+/// configured community pool
+pub async fn buy_exact_out(
+    context: &mut ProgramTestContext,
+    buyer: &Keypair,
+    mint: &Pubkey,
+    launch: &Pubkey,
+    token_program: Pubkey,
+    token_amount: u64,
+    max_sol_in: u64,
+) -> Result<(), BanksClientError> {
+    let community_pool = get_config(context).await.community_pool;
+    let piecewise_curve = piecewise_curve(context, launch).await;
+    let data = launchpad::instruction::BuyTokensExactOut {
+        token_amount,
+        max_sol_in,
+        presale_proof: None,
+    }
+    .data();
+    let buy = buy_tokens_instruction(buyer, mint, launch, &community_pool, token_program, piecewise_curve, data);
+    process(context, &[buy], &[buyer]).await
+}
+
+/// Sell `token_amount` back to the curve
+pub async fn sell(
+    context: &mut ProgramTestContext,
+    seller: &Keypair,
+    mint: &Pubkey,
+    launch: &Pubkey,
+    token_program: Pubkey,
+    token_amount: u64,
+) -> Result<(), BanksClientError> {
+    let data = launchpad::instruction::SellTokens {
+        token_amount,
+        min_sol_out: 0,
+    }
+    .data();
+    let piecewise_curve = piecewise_curve(context, launch).await;
+    let sell = sell_instruction(seller, mint, launch, token_program, piecewise_curve, data);
+    process(context, &[sell], &[seller]).await
+}
+
+/// Sell just enough back to the curve to receive `sol_amount`, at most `max_tokens_in`
+pub async fn sell_exact_out(
+    context: &mut ProgramTestContext,
+    seller: &Keypair,
+    mint: &Pubkey,
+    launch: &Pubkey,
+    token_program: Pubkey,
+    sol_amount: u64,
+    max_tokens_in: u64,
+) -> Result<(), BanksClientError> {
+    let data = launchpad::instruction::SellTokensExactOut {
+        sol_amount,
+        max_tokens_in,
+    }
+    .data();
+    let piecewise_curve = piecewise_curve(context, launch).await;
+    let sell = sell_instruction(seller, mint, launch, token_program, piecewise_curve, data);
+    process(context, &[sell], &[seller]).await
+}
+
+fn sell_instruction(
+    seller: &Keypair,
+    mint: &Pubkey,
+    launch: &Pubkey,
+    token_program: Pubkey,
+    piecewise_curve: Option<Pubkey>,
+    data: Vec<u8>,
+) -> Instruction {
+    Instruction {
+        program_id: launchpad::ID,
+        accounts: launchpad::accounts::SellTokens {
+            launch: *launch,
+            mint: *mint,
+            launch_token_account: get_associated_token_address_with_program_id(launch, mint, &token_program),
+            piecewise_curve,
+            user_position: position_address(launch, &seller.pubkey()),
+            seller_token_account: get_associated_token_address_with_program_id(&seller.pubkey(), mint, &token_program),
+            seller: seller.pubkey(),
+            token_program,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data,
+    }
+}
+
+/// Cancel `launch` signed by `authority`
+pub async fn cancel_launch(
+    context: &mut ProgramTestContext,
+    authority: &Keypair,
+    launch: &Pubkey,
+) -> Result<(), BanksClientError> {
+    let cancel = Instruction {
+        program_id: launchpad::ID,
+        accounts: launchpad::accounts::CancelLaunch {
+            launch: *launch,
+            config: config_address(),
+            authority: authority.pubkey(),
+        }
+        .to_account_metas(None),
+        data: launchpad::instruction::CancelLaunch {}.data(),
+    };
+
+    process(context, &[cancel], &[authority]).await
+}
+
+/// Mark `launch` Failed past its deadline, which needs no signature beyond the payer's
+pub async fn fail_launch(context: &mut ProgramTestContext, launch: &Pubkey) -> Result<(), BanksClientError> {
+    let fail = Instruction {
+        program_id: launchpad::ID,
+        accounts: launchpad::accounts::FailLaunch { launch: *launch }.to_account_metas(None),
+        data: launchpad::instruction::FailLaunch {}.data(),
+    };
+
+    process(context, &[fail], &[]).await
+}
+
+/// Return `holder`'s tokens to a cancelled or failed launch for a refund
+pub async fn claim_refund(
+    context: &mut ProgramTestContext,
+    holder: &Keypair,
+    mint: &Pubkey,
+    launch: &Pubkey,
+    token_program: Pubkey,
+) -> Result<(), BanksClientError> {
+    let claim = Instruction {
+        program_id: launchpad::ID,
+        accounts: launchpad::accounts::ClaimRefund {
+            launch: *launch,
+            mint: *mint,
+            launch_token_account: get_associated_token_address_with_program_id(launch, mint, &token_program),
+            user_position: position_address(launch, &holder.pubkey()),
+            holder_token_account: get_associated_token_address_with_program_id(&holder.pubkey(), mint, &token_program),
+            holder: holder.pubkey(),
+            token_program,
+        }
+        .to_account_metas(None),
+        data: launchpad::instruction::ClaimRefund {}.data(),
+    };
+
+    process(context, &[claim], &[holder]).await
+}
+
+/// Update the launch's metadata signed by `creator`, through Metaplex for SPL Token mints
+pub async fn update_launch_metadata(
+    context: &mut ProgramTestContext,
+    creator: &Keypair,
+    mint: &Pubkey,
+    launch: &Pubkey,
+    token_program: Pubkey,
+    name: Option<&str>,
+    uri: Option<&str>,
+) -> Result<(), BanksClientError> {
+    let (metadata, token_metadata_program) = metadata_accounts(mint, token_program);
+    let update = Instruction {
+        program_id: launchpad::ID,
+        accounts: launchpad::accounts::UpdateLaunchMetadata {
+            launch: *launch,
+            mint: *mint,
+            config: config_address(),
+            creator: creator.pubkey(),
+            metadata,
+            token_metadata_program,
+            token_program,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: launchpad::instruction::UpdateLaunchMetadata {
+            name: name.map(str::to_string),
+            symbol: None,
+            uri: uri.map(str::to_string),
+        }
+        .data(),
+    };
+
+    process(context, &[update], &[creator]).await
+}
+
+/// Propose `new_creator` for `launch`, signed by `creator`
+pub async fn propose_creator(
+    context: &mut ProgramTestContext,
+    creator: &Keypair,
+    launch: &Pubkey,
+    new_creator: &Pubkey,
+) -> Result<(), BanksClientError> {
+    let propose = Instruction {
+        program_id: launchpad::ID,
+        accounts: launchpad::accounts::ProposeCreator {
+            launch: *launch,
+            creator: creator.pubkey(),
+        }
+        .to_account_metas(None),
+        data: launchpad::instruction::ProposeCreator { new_creator: *new_creator }.data(),
+    };
+
+    process(context, &[propose], &[creator]).await
+}
+
+/// Accept the creator role of `launch` as `new_creator`
+pub async fn accept_creator(
+    context: &mut ProgramTestContext,
+    new_creator: &Keypair,
+    launch: &Pubkey,
+) -> Result<(), BanksClientError> {
+    let accept = Instruction {
+        program_id: launchpad::ID,
+        accounts: launchpad::accounts::AcceptCreator {
+            launch: *launch,
+            new_creator: new_creator.pubkey(),
+        }
+        .to_account_metas(None),
+        data: launchpad::instruction::AcceptCreator {}.data(),
+    };
+
+    process(context, &[accept], &[new_creator]).await
+}
+
+/// Migrate a completed `launch` to a stand-in pool, signed by `authority`. The pool's address
+/// is kept in the launch, its tokens in its associated token account and its SOL on the pool
+/// address itself
+pub async fn migrate(
+    context: &mut ProgramTestContext,
+    authority: &Keypair,
+    mint: &Pubkey,
+    launch: &Pubkey,
+    token_program: Pubkey,
+) -> Result<(), BanksClientError> {
+    let piecewise_curve = piecewise_curve(context, launch).await;
+    let (metadata, token_metadata_program) = metadata_accounts(mint, token_program);
+    let pool = Pubkey::new_unique();
+    let create_pool_token_account = create_associated_token_account_idempotent(
+        &context.payer.pubkey(),
+        &pool,
+        mint,
+        &token_program,
+    );
+
+    let migrate = Instruction {
+        program_id: launchpad::ID,
+        accounts: launchpad::accounts::MigrateToPool {
+            launch: *launch,
+            config: config_address(),
+            mint: *mint,
+            launch_token_account: get_associated_token_address_with_program_id(launch, mint, &token_program),
+            piecewise_curve,
+            pool,
+            pool_token_account: get_associated_token_address_with_program_id(&pool, mint, &token_program),
+            pool_sol_account: pool,
+            authority: authority.pubkey(),
+            metadata,
+            token_metadata_program,
+            token_program,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: launchpad::instruction::MigrateToPool {}.data(),
+    };
+
+    process(context, &[create_pool_token_account, migrate], &[authority]).await
+}
+
+/// Claim the creator's vested tokens of `launch`
+pub async fn claim_vested_tokens(
+    context: &mut ProgramTestContext,
+    creator: &Keypair,
+    mint: &Pubkey,
+    launch: &Pubkey,
+    token_program: Pubkey,
+) -> Result<(), BanksClientError> {
+    let claim = Instruction {
+        program_id: launchpad::ID,
+        accounts: launchpad::accounts::ClaimVestedTokens {
+            launch: *launch,
+            mint: *mint,
+            vesting_vault: vesting_vault_address(launch),
+            creator_token_account: get_associated_token_address_with_program_id(&creator.pubkey(), mint, &token_program),
+            creator: creator.pubkey(),
+            token_program,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: launchpad::instruction::ClaimVestedTokens {}.data(),
+    };
+
+    process(context, &[claim], &[creator]).await
+}
+
+/// Release the vested tokens of allocation `index` of `launch` to `beneficiary`, cranked
+/// and paid for by `payer`
+pub async fn claim_allocation(
+    context: &mut ProgramTestContext,
+    payer: &Keypair,
+    mint: &Pubkey,
+    launch: &Pubkey,
+    token_program: Pubkey,
+    beneficiary: &Pubkey,
+    index: u8,
+) -> Result<(), BanksClientError> {
+    let claim = Instruction {
+        program_id: launchpad::ID,
+        accounts: launchpad::accounts::ClaimAllocation {
+            launch: *launch,
+            mint: *mint,
+            allocation_table: allocation_table_address(launch),
+            allocation_vault: allocation_vault_address(launch, index),
+            beneficiary: *beneficiary,
+            beneficiary_token_account: get_associated_token_address_with_program_id(beneficiary, mint, &token_program),
+            payer: payer.pubkey(),
+            token_program,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        data: launchpad::instruction::ClaimAllocation { index }.data(),
+    };
+
+    process(context, &[claim], &[payer]).await
+}
+
+/// Burn the vesting vault of a cancelled or failed `launch`, which needs no signature beyond
+/// the payer's
+pub async fn burn_vesting_tokens(
+    context: &mut ProgramTestContext,
+    mint: &Pubkey,
+    launch: &Pubkey,
+    token_program: Pubkey,
+) -> Result<(), BanksClientError> {
+    let burn = Instruction {
+        program_id: launchpad::ID,
+        accounts: launchpad::accounts::BurnVestingTokens {
+            launch: *launch,
+            mint: *mint,
+            vesting_vault: vesting_vault_address(launch),
+            token_program,
+        }
+        .to_account_metas(None),
+        data: launchpad::instruction::BurnVestingTokens {}.data(),
+    };
+
+    process(context, &[burn], &[]).await
+}
+
+/// Burn allocation bucket `index` of a cancelled or failed `launch`
+pub async fn burn_allocation_tokens(
+    context: &mut ProgramTestContext,
+    mint: &Pubkey,
+    launch: &Pubkey,
+    token_program: Pubkey,
+    index: u8,
+) -> Result<(), BanksClientError> {
+    let burn = Instruction {
+        program_id: launchpad::ID,
+        accounts: launchpad::accounts::BurnAllocationTokens {
+            launch: *launch,
+            mint: *mint,
+            allocation_vault: allocation_vault_address(launch, index),
+            token_program,
+        }
+        .to_account_metas(None),
+        data: launchpad::instruction::BurnAllocationTokens { index }.data(),
+    };
+
+    process(context, &[burn], &[]).await
+}
@@ -0,0 +1,154 @@
+//! Stand-in for the Token Metadata program when `scripts/fetch-fixtures.sh` hasn't been run.
+//!
+//! Handles the two instructions launches send, `CreateMetadataAccountV3` and
+//! `UpdateMetadataAccountV2`, with the checks the program relies on, so SPL Token launches
+//! run without the mainnet binary. `tests/metadata.rs` checks the real one when it's there.
+
+use anchor_lang::solana_program::account_info::AccountInfo;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::program_error::ProgramError;
+use anchor_lang::solana_program::rent::Rent;
+use anchor_lang::solana_program::sysvar::Sysvar;
+use anchor_lang::{AnchorDeserialize, AnchorSerialize};
+use anchor_spl::metadata::mpl_token_metadata::accounts::Metadata;
+use anchor_spl::metadata::mpl_token_metadata::errors::MplTokenMetadataError;
+use anchor_spl::metadata::mpl_token_metadata::instructions::{
+    CreateMetadataAccountV3InstructionArgs, UpdateMetadataAccountV2InstructionArgs,
+};
+use anchor_spl::metadata::mpl_token_metadata::types::Key;
+use anchor_spl::token_2022::spl_token_2022::extension::StateWithExtensions;
+use anchor_spl::token_2022::spl_token_2022::state::Mint;
+use solana_sdk::program_option::COption;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::system_instruction;
+
+const CREATE_METADATA_ACCOUNT_V3: u8 = 33;
+const UPDATE_METADATA_ACCOUNT_V2: u8 = 15;
+
+/// Size the real program allocates for every metadata account
+const METADATA_LEN: usize = 679;
+
+pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    match data.split_first() {
+        Some((&CREATE_METADATA_ACCOUNT_V3, args)) => create_metadata(program_id, accounts, args),
+        Some((&UPDATE_METADATA_ACCOUNT_V2, args)) => update_metadata(program_id, accounts, args),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn error(error: MplTokenMetadataError) -> ProgramError {
+    ProgramError::Custom(error as u32)
+}
+
+fn create_metadata(program_id: &Pubkey, accounts: &[AccountInfo], args: &[u8]) -> ProgramResult {
+    let [metadata, mint, mint_authority, payer, update_authority, system_program, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let args = CreateMetadataAccountV3InstructionArgs::try_from_slice(args)?;
+
+    let (address, bump) = Metadata::find_pda(mint.key);
+    if address != *metadata.key {
+        return Err(error(MplTokenMetadataError::InvalidMetadataKey));
+    }
+    if !metadata.data_is_empty() {
+        return Err(error(MplTokenMetadataError::AlreadyInitialized));
+    }
+
+    let mint_data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+    if mint_state.base.mint_authority != COption::Some(*mint_authority.key) || !mint_authority.is_signer {
+        return Err(error(MplTokenMetadataError::InvalidMintAuthority));
+    }
+    drop(mint_data);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            metadata.key,
+            Rent::get()?.minimum_balance(METADATA_LEN),
+            METADATA_LEN as u64,
+            program_id,
+        ),
+        &[payer.clone(), metadata.clone(), system_program.clone()],
+        &[&[b"metadata", program_id.as_ref(), mint.key.as_ref(), &[bump]]],
+    )?;
+
+    let state = Metadata {
+        key: Key::MetadataV1,
+        update_authority: *update_authority.key,
+        mint: *mint.key,
+        name: args.data.name,
+        symbol: args.data.symbol,
+        uri: args.data.uri,
+        seller_fee_basis_points: args.data.seller_fee_basis_points,
+        creators: args.data.creators,
+        primary_sale_happened: false,
+        is_mutable: args.is_mutable,
+        edition_nonce: None,
+        token_standard: None,
+        collection: args.data.collection,
+        uses: args.data.uses,
+        collection_details: args.collection_details,
+        programmable_config: None,
+    };
+    write_metadata(metadata, &state)
+}
+
+fn update_metadata(program_id: &Pubkey, accounts: &[AccountInfo], args: &[u8]) -> ProgramResult {
+    let [metadata, update_authority, ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let args = UpdateMetadataAccountV2InstructionArgs::try_from_slice(args)?;
+
+    if metadata.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let mut state = Metadata::safe_deserialize(&metadata.try_borrow_data()?)
+        .map_err(|_| error(MplTokenMetadataError::Uninitialized))?;
+    if state.update_authority != *update_authority.key {
+        return Err(error(MplTokenMetadataError::UpdateAuthorityIncorrect));
+    }
+    if !update_authority.is_signer {
+        return Err(error(MplTokenMetadataError::UpdateAuthorityIsNotSigner));
+    }
+
+    if let Some(data) = args.data {
+        if !state.is_mutable {
+            return Err(error(MplTokenMetadataError::DataIsImmutable));
+        }
+        state.name = data.name;
+        state.symbol = data.symbol;
+        state.uri = data.uri;
+        state.seller_fee_basis_points = data.seller_fee_basis_points;
+        state.creators = data.creators;
+        state.collection = data.collection;
+        state.uses = data.uses;
+    }
+    if let Some(new_update_authority) = args.new_update_authority {
+        state.update_authority = new_update_authority;
+    }
+    if let Some(primary_sale_happened) = args.primary_sale_happened {
+        state.primary_sale_happened |= primary_sale_happened;
+    }
+    if let Some(is_mutable) = args.is_mutable {
+        // Metadata can be locked, never unlocked
+        if is_mutable && !state.is_mutable {
+            return Err(error(MplTokenMetadataError::IsMutableCanOnlyBeFlippedToFalse));
+        }
+        state.is_mutable = is_mutable;
+    }
+
+    write_metadata(metadata, &state)
+}
+
+fn write_metadata(metadata: &AccountInfo, state: &Metadata) -> ProgramResult {
+    let serialized = state.try_to_vec()?;
+    let mut data = metadata.try_borrow_mut_data()?;
+    if serialized.len() > data.len() {
+        return Err(error(MplTokenMetadataError::InstructionPackError));
+    }
+    data.fill(0);
+    data[..serialized.len()].copy_from_slice(&serialized);
+    Ok(())
+}
@@ -0,0 +1,90 @@
+//! Compute units the curve math costs on chain. They're only metered when the program runs
+//! from the built `.so`, so the tests skip themselves otherwise:
+//!
+//!     anchor build
+//!     BPF_OUT_DIR=target/deploy cargo test -p launchpad --test compute -- --nocapture
+
+mod common;
+
+use common::*;
+use launchpad::fixed_point::{Q64x64, Rounding};
+use launchpad::state::{CurvePoint, PiecewiseCurve};
+
+/// Units an instruction gets without a compute budget request
+const DEFAULT_COMPUTE_UNITS: u64 = 200_000;
+
+#[tokio::test]
+async fn test_linear_buy_fits_default_compute_budget() {
+    if !runs_bpf() {
+        eprintln!("skipping: compute units are only metered with BPF_OUT_DIR holding launchpad.so");
+        return;
+    }
+
+    for token_program in TOKEN_PROGRAMS {
+        let mut context = program_test().start_with_context().await;
+        let creator = context.payer.insecure_clone();
+
+        let community_pool = initialize(&mut context).await;
+        let buyer = wallet(&mut context, 100 * LAMPORTS_PER_SOL).await;
+        let (mint, launch) = create_launch(&mut context, &creator, token_program, None).await;
+
+        // Buying by SOL amount inverts the curve's area to find the tokens out; the first
+        // buy also opens the buyer's token account and position
+        let buy = buy_instruction(&buyer, &mint, &launch, &community_pool, token_program, FUND_RAISING / 3, None);
+        let units = compute_units(&mut context, std::slice::from_ref(&buy), &[&buyer]).await;
+        eprintln!("Linear buy under {}: {} compute units", token_program, units);
+        assert!(units <= DEFAULT_COMPUTE_UNITS);
+
+        process(&mut context, &[buy], &[&buyer]).await.unwrap();
+        assert!(get_launch(&mut context, &launch).await.tokens_sold > 0);
+    }
+}
+
+#[tokio::test]
+async fn test_piecewise_buy_through_max_points_fits_default_compute_budget() {
+    if !runs_bpf() {
+        eprintln!("skipping: compute units are only metered with BPF_OUT_DIR holding launchpad.so");
+        return;
+    }
+
+    // Evenly spaced breakpoints on a line from 0.5x to 1.5x of the average price the target
+    // needs, padded by one millionth so the curve clears the target despite rounding down
+    let segments = PiecewiseCurve::MAX_POINTS as u128 - 1;
+    let curve_points: Vec<CurvePoint> = (0..=segments)
+        .map(|step| {
+            let progress_bps = (step * PiecewiseCurve::FULL_PROGRESS_BPS as u128 / segments) as u16;
+            let price = Q64x64::from_ratio(
+                FUND_RAISING as u128 * (5_000 + progress_bps as u128) * 1_000_001,
+                SELL_AMOUNT as u128 * 10_000 * 1_000_000,
+                Rounding::Up,
+            )
+            .unwrap()
+            .raw();
+            CurvePoint { progress_bps, price }
+        })
+        .collect();
+
+    for token_program in TOKEN_PROGRAMS {
+        let mut context = program_test().start_with_context().await;
+        let creator = context.payer.insecure_clone();
+
+        let community_pool = initialize(&mut context).await;
+        let buyer = wallet(&mut context, 100 * LAMPORTS_PER_SOL).await;
+        let (mint, launch) =
+            create_piecewise_launch(&mut context, &creator, token_program, curve_points.clone()).await.unwrap();
+
+        // Most of the target in one buy lands in the last segments
+        let buy = piecewise_buy_instruction(&buyer, &mint, &launch, &community_pool, token_program, FUND_RAISING * 9 / 10);
+        let units = compute_units(&mut context, std::slice::from_ref(&buy), &[&buyer]).await;
+        eprintln!(
+            "Piecewise buy through {} points under {}: {} compute units",
+            PiecewiseCurve::MAX_POINTS,
+            token_program,
+            units
+        );
+        assert!(units <= DEFAULT_COMPUTE_UNITS);
+
+        process(&mut context, &[buy], &[&buyer]).await.unwrap();
+        assert!(get_launch(&mut context, &launch).await.tokens_sold > SELL_AMOUNT / 2);
+    }
+}
@@ -0,0 +1,85 @@
+//! Admin updates to the launchpad config and its two-step authority transfer.
+//!
+//!     cargo test -p launchpad --test config
+
+mod common;
+
+use common::*;
+use launchpad::errors::LaunchpadError;
+use launchpad::state::LaunchpadConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+
+#[tokio::test]
+async fn test_update_config() {
+    let mut context = program_test().start_with_context().await;
+    let admin = context.payer.insecure_clone();
+
+    let community_pool = initialize(&mut context).await;
+    let stranger = wallet(&mut context, LAMPORTS_PER_SOL).await;
+    let new_pool = Pubkey::new_unique();
+
+    assert_error(
+        update_config(&mut context, &stranger, Some(200), None, None).await,
+        LaunchpadError::InvalidAuthority,
+    );
+    assert_error(
+        update_config(&mut context, &admin, Some(LaunchpadConfig::MAX_FEE_BPS + 1), None, None).await,
+        LaunchpadError::InvalidFeePercentage,
+    );
+    assert_error(
+        update_config(&mut context, &admin, None, None, Some(LaunchpadConfig::MAX_FEE_BPS + 1)).await,
+        LaunchpadError::InvalidFeePercentage,
+    );
+
+    // Fields left out keep their value
+    let before = get_config(&mut context).await;
+    update_config(&mut context, &admin, Some(200), None, None).await.unwrap();
+    let config = get_config(&mut context).await;
+    assert_eq!(config.fee_percentage, 200);
+    assert_eq!(config.community_pool, community_pool);
+    assert_eq!(config.referral_fee, before.referral_fee);
+
+    update_config(&mut context, &admin, None, Some(new_pool), Some(20)).await.unwrap();
+    let config = get_config(&mut context).await;
+    assert_eq!(config.fee_percentage, 200);
+    assert_eq!(config.community_pool, new_pool);
+    assert_eq!(config.referral_fee, 20);
+}
+
+#[tokio::test]
+async fn test_config_authority_two_steps() {
+    let mut context = program_test().start_with_context().await;
+    let admin = context.payer.insecure_clone();
+
+    initialize(&mut context).await;
+    let new_admin = wallet(&mut context, LAMPORTS_PER_SOL).await;
+    let stranger = wallet(&mut context, LAMPORTS_PER_SOL).await;
+
+    // Only the authority can propose, and proposing alone hands nothing over
+    assert_error(
+        propose_authority(&mut context, &stranger, &stranger.pubkey()).await,
+        LaunchpadError::InvalidAuthority,
+    );
+    propose_authority(&mut context, &admin, &new_admin.pubkey()).await.unwrap();
+    let config = get_config(&mut context).await;
+    assert_eq!(config.authority, admin.pubkey());
+    assert_eq!(config.pending_authority, new_admin.pubkey());
+
+    // Only the proposed key can accept
+    assert_error(accept_authority(&mut context, &stranger).await, LaunchpadError::InvalidAuthority);
+    accept_authority(&mut context, &new_admin).await.unwrap();
+    let config = get_config(&mut context).await;
+    assert_eq!(config.authority, new_admin.pubkey());
+    assert_eq!(config.pending_authority, Pubkey::default());
+
+    // The old authority is out, the new one is in
+    assert_error(
+        update_config(&mut context, &admin, Some(200), None, None).await,
+        LaunchpadError::InvalidAuthority,
+    );
+    update_config(&mut context, &new_admin, Some(200), None, None).await.unwrap();
+
+    // Accepting again after the handover does nothing
+    assert_error(accept_authority(&mut context, &new_admin).await, LaunchpadError::InvalidAuthority);
+}
@@ -0,0 +1,60 @@
+//! Handing the creator role over in two steps.
+//!
+//!     cargo test -p launchpad --test creator
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use launchpad::errors::LaunchpadError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+
+#[tokio::test]
+async fn test_creator_transfer_two_steps() {
+    for token_program in TOKEN_PROGRAMS {
+        let mut context = program_test().start_with_context().await;
+
+        initialize(&mut context).await;
+        let creator = wallet(&mut context, 10 * LAMPORTS_PER_SOL).await;
+        let new_creator = wallet(&mut context, LAMPORTS_PER_SOL).await;
+        let stranger = wallet(&mut context, LAMPORTS_PER_SOL).await;
+        let (mint, launch) = create_launch(&mut context, &creator, token_program, None).await;
+
+        // Only the creator can propose, and proposing alone hands nothing over
+        assert_error(
+            propose_creator(&mut context, &stranger, &launch, &stranger.pubkey()).await,
+            LaunchpadError::InvalidAuthority,
+        );
+        propose_creator(&mut context, &creator, &launch, &new_creator.pubkey()).await.unwrap();
+
+        let launch_state = get_launch(&mut context, &launch).await;
+        assert_eq!(launch_state.creator, creator.pubkey());
+        assert_eq!(launch_state.pending_creator, new_creator.pubkey());
+
+        // Only the pending creator can accept
+        assert_error(accept_creator(&mut context, &stranger, &launch).await, LaunchpadError::InvalidAuthority);
+        assert_error(accept_creator(&mut context, &creator, &launch).await, LaunchpadError::InvalidAuthority);
+        accept_creator(&mut context, &new_creator, &launch).await.unwrap();
+
+        let launch_state = get_launch(&mut context, &launch).await;
+        assert_eq!(launch_state.creator, new_creator.pubkey());
+        assert_eq!(launch_state.pending_creator, Pubkey::default());
+
+        // The proposal is spent
+        assert_error(accept_creator(&mut context, &new_creator, &launch).await, LaunchpadError::InvalidAuthority);
+
+        // The old creator lost the launch settings, the new one has them
+        let new_uri = "https://test.com/metadata/new-creator.json";
+        assert_error(
+            update_launch_metadata(&mut context, &creator, &mint, &launch, token_program, None, Some(new_uri)).await,
+            ErrorCode::ConstraintHasOne,
+        );
+        assert_error(
+            propose_creator(&mut context, &creator, &launch, &creator.pubkey()).await,
+            LaunchpadError::InvalidAuthority,
+        );
+        update_launch_metadata(&mut context, &new_creator, &mint, &launch, token_program, None, Some(new_uri)).await.unwrap();
+        assert_eq!(get_launch(&mut context, &launch).await.uri, new_uri);
+    }
+}
@@ -0,0 +1,174 @@
+//! Trading and migrating launches on each curve type through the program.
+//!
+//!     cargo test -p launchpad --test curves
+
+mod common;
+
+use anchor_lang::AccountDeserialize;
+use common::*;
+use launchpad::errors::LaunchpadError;
+use launchpad::fixed_point::{Q64x64, Rounding};
+use launchpad::state::{CreateLaunchParams, CurvePoint, CurveType, Launch, LaunchStatus, PiecewiseCurve};
+use solana_sdk::signature::Signer;
+
+/// Price at `quarters / 4` of the average price the target needs, padded by one millionth
+/// so the curve clears the target despite rounding down
+fn piecewise_price(quarters: u128) -> u128 {
+    Q64x64::from_ratio(
+        FUND_RAISING as u128 * quarters * 1_000_001,
+        SELL_AMOUNT as u128 * 4 * 1_000_000,
+        Rounding::Up,
+    )
+    .unwrap()
+    .raw()
+}
+
+/// Breakpoints raising the target: 0.5x at 0%, 0.75x at 25%, 1.25x at 75% and 1.5x at 100%
+fn curve_points() -> Vec<CurvePoint> {
+    vec![
+        CurvePoint { progress_bps: 0, price: piecewise_price(2) },
+        CurvePoint { progress_bps: 2_500, price: piecewise_price(3) },
+        CurvePoint { progress_bps: 7_500, price: piecewise_price(5) },
+        CurvePoint { progress_bps: 10_000, price: piecewise_price(6) },
+    ]
+}
+
+#[tokio::test]
+async fn test_piecewise_launch_rejects_bad_segments() {
+    for token_program in TOKEN_PROGRAMS {
+        let mut context = program_test().start_with_context().await;
+        let creator = context.payer.insecure_clone();
+        initialize(&mut context).await;
+
+        let mut short_of_full = curve_points();
+        short_of_full.last_mut().unwrap().progress_bps = 9_999;
+        let mut empty_segment = curve_points();
+        empty_segment[1].progress_bps = 0;
+        let mut free_start = curve_points();
+        free_start[0].price = 0;
+        for bad in [vec![], curve_points()[..1].to_vec(), short_of_full, empty_segment, free_start] {
+            let result = create_piecewise_launch(&mut context, &creator, token_program, bad).await;
+            assert_error(result.map(|_| ()), LaunchpadError::InvalidCurvePoints);
+        }
+
+        // Half the prices fall short of the target, double overshoot it
+        for scale in [(1, 2), (2, 1)] {
+            let points = curve_points()
+                .into_iter()
+                .map(|point| CurvePoint { price: point.price * scale.0 / scale.1, ..point })
+                .collect();
+            let result = create_piecewise_launch(&mut context, &creator, token_program, points).await;
+            assert_error(result.map(|_| ()), LaunchpadError::InvalidFundRaisingTarget);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_piecewise_buy_sell_migrate() {
+    for token_program in TOKEN_PROGRAMS {
+        let mut context = program_test().start_with_context().await;
+        let admin = context.payer.insecure_clone();
+
+        let community_pool = initialize(&mut context).await;
+        let creator = wallet(&mut context, 10 * LAMPORTS_PER_SOL).await;
+        let buyer = wallet(&mut context, 1_000 * LAMPORTS_PER_SOL).await;
+
+        let points = curve_points();
+        let (mint, launch) = create_piecewise_launch(&mut context, &creator, token_program, points.clone()).await.unwrap();
+
+        // The breakpoints live in the launch's companion account
+        let address = piecewise_curve(&mut context, &launch).await.expect("piecewise curve account");
+        let account = context.banks_client.get_account(address).await.unwrap().unwrap();
+        let curve = PiecewiseCurve::try_deserialize(&mut account.data.as_slice()).unwrap();
+        assert_eq!(curve.launch, launch);
+        assert!(curve.points == points);
+
+        // Buys and sells are priced along the segments
+        let sol_amount = 10 * LAMPORTS_PER_SOL;
+        let net_sol = sol_amount - sol_amount * FEE_BPS as u64 / 10_000;
+        let expected = get_launch(&mut context, &launch).await.calculate_tokens_for_sol(net_sol, &points).unwrap();
+        buy(&mut context, &buyer, &mint, &launch, &community_pool, token_program, sol_amount).await.unwrap();
+        let bought = token_balance(&mut context, &buyer.pubkey(), &mint, token_program).await;
+        assert_eq!(bought, expected);
+
+        let launch_state = get_launch(&mut context, &launch).await;
+        let sol_back = launch_state.calculate_sol_for_tokens(bought / 2, &points).unwrap();
+        let buyer_before = balance(&mut context, &buyer.pubkey()).await;
+        sell(&mut context, &buyer, &mint, &launch, token_program, bought / 2).await.unwrap();
+        assert_eq!(balance(&mut context, &buyer.pubkey()).await - buyer_before, sol_back);
+        assert_eq!(get_launch(&mut context, &launch).await.sol_raised, launch_state.sol_raised - sol_back);
+
+        // Selling out completes the curve and the pool opens where the last segment ends
+        buy(&mut context, &buyer, &mint, &launch, &community_pool, token_program, 2 * FUND_RAISING).await.unwrap();
+        let completed = get_launch(&mut context, &launch).await;
+        assert!(completed.status == LaunchStatus::Completed);
+
+        let launch_tokens = token_balance(&mut context, &launch, &mint, token_program).await;
+        let unsold = SELL_AMOUNT - completed.tokens_sold;
+        let (pool_tokens, pool_sol) = completed.migration_pool(launch_tokens - unsold, &points).unwrap();
+        assert_eq!(pool_sol, completed.sol_raised);
+
+        migrate(&mut context, &admin, &mint, &launch, token_program).await.unwrap();
+        let migrated = get_launch(&mut context, &launch).await;
+        assert!(migrated.status == LaunchStatus::Migrated);
+        assert_eq!(token_balance(&mut context, &migrated.pool_address, &mint, token_program).await, pool_tokens);
+        assert_eq!(token_balance(&mut context, &launch, &mint, token_program).await, 0);
+    }
+}
+
+#[tokio::test]
+async fn test_constant_product_buy_sell_migrate() {
+    for token_program in TOKEN_PROGRAMS {
+        let mut context = program_test().start_with_context().await;
+        let admin = context.payer.insecure_clone();
+
+        let community_pool = initialize(&mut context).await;
+        let creator = wallet(&mut context, 10 * LAMPORTS_PER_SOL).await;
+        let buyer = wallet(&mut context, 1_000 * LAMPORTS_PER_SOL).await;
+
+        let params = CreateLaunchParams { curve_type: CurveType::ConstantProduct, ..launch_params(None) };
+        let (mint, launch) = create_launch_with(&mut context, &creator, token_program, params).await;
+
+        // Virtual reserves sized so the curve ends at the price of a pool holding the rest of supply
+        let launch_state = get_launch(&mut context, &launch).await;
+        let (virtual_sol, virtual_tokens) = Launch::constant_product_reserves(SUPPLY, SELL_AMOUNT, FUND_RAISING).unwrap();
+        assert_eq!(launch_state.virtual_sol_reserves, virtual_sol);
+        assert_eq!(launch_state.virtual_token_reserves, virtual_tokens);
+        let pool_supply = SUPPLY - SELL_AMOUNT;
+        assert_eq!(token_balance(&mut context, &launch, &mint, token_program).await, SUPPLY);
+        assert_eq!(launch_state.migration_allocation(&[]).unwrap(), pool_supply);
+
+        // Buys take the closed-form position on the reserves, sells walk it back from the top
+        let sol_amount = 10 * LAMPORTS_PER_SOL;
+        let net_sol = sol_amount - sol_amount * FEE_BPS as u64 / 10_000;
+        let expected = launch_state.calculate_tokens_for_sol(net_sol, &[]).unwrap();
+        buy(&mut context, &buyer, &mint, &launch, &community_pool, token_program, sol_amount).await.unwrap();
+        let bought = token_balance(&mut context, &buyer.pubkey(), &mint, token_program).await;
+        assert_eq!(bought, expected);
+
+        let launch_state = get_launch(&mut context, &launch).await;
+        let sol_back = launch_state.calculate_sol_for_tokens(bought / 2, &[]).unwrap();
+        assert!(sol_back > net_sol / 2 && sol_back < net_sol);
+        let buyer_before = balance(&mut context, &buyer.pubkey()).await;
+        sell(&mut context, &buyer, &mint, &launch, token_program, bought / 2).await.unwrap();
+        assert_eq!(balance(&mut context, &buyer.pubkey()).await - buyer_before, sol_back);
+
+        // The curve only raises the target by selling out, after which the pool takes the rest of
+        // supply with all the SOL, at the curve's final price
+        buy(&mut context, &buyer, &mint, &launch, &community_pool, token_program, 2 * FUND_RAISING).await.unwrap();
+        let completed = get_launch(&mut context, &launch).await;
+        assert!(completed.status == LaunchStatus::Completed);
+        assert_eq!(completed.tokens_sold, SELL_AMOUNT);
+        assert!(completed.sol_raised >= FUND_RAISING);
+
+        let (pool_tokens, pool_sol) = completed.migration_pool(pool_supply, &[]).unwrap();
+        assert_eq!(pool_sol, completed.sol_raised);
+        assert!(pool_tokens <= pool_supply && pool_supply - pool_tokens <= pool_supply / 1_000_000);
+
+        migrate(&mut context, &admin, &mint, &launch, token_program).await.unwrap();
+        let migrated = get_launch(&mut context, &launch).await;
+        assert!(migrated.status == LaunchStatus::Migrated);
+        assert_eq!(token_balance(&mut context, &migrated.pool_address, &mint, token_program).await, pool_tokens);
+        assert_eq!(token_balance(&mut context, &launch, &mint, token_program).await, 0);
+    }
+}
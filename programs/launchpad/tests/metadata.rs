@@ -0,0 +1,159 @@
+//! Launch metadata: Metaplex for SPL Token mints, on-mint metadata for Token-2022.
+//!
+//! SPL Token tests run against a stand-in for Token Metadata, the Metaplex test checks the real
+//! program and skips itself until it's been fetched into `tests/fixtures`:
+//!
+//!     scripts/fetch-fixtures.sh
+//!     cargo test -p launchpad --test metadata
+
+mod common;
+
+use anchor_spl::metadata::mpl_token_metadata;
+use anchor_spl::token_2022::spl_token_2022::extension::{BaseStateWithExtensions, StateWithExtensions};
+use anchor_spl::token_2022::spl_token_2022::state::Mint;
+use common::*;
+use launchpad::errors::LaunchpadError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+use launchpad::state::LaunchStatus;
+use spl_token_metadata_interface::state::TokenMetadata;
+
+#[tokio::test]
+async fn test_create_launch_creates_metadata() {
+    if !has_metadata_fixture() {
+        eprintln!("skipping: {} not found, run scripts/fetch-fixtures.sh", METADATA_FIXTURE);
+        return;
+    }
+
+    let mut context = program_test().start_with_context().await;
+    let creator = context.payer.insecure_clone();
+
+    initialize(&mut context).await;
+    let (mint, launch) = create_launch(&mut context, &creator, anchor_spl::token::ID, None).await;
+
+    let (metadata, _) = mpl_token_metadata::accounts::Metadata::find_pda(&mint);
+    let account = context.banks_client.get_account(metadata).await.unwrap().expect("metadata account");
+    assert_eq!(account.owner, mpl_token_metadata::ID);
+
+    let metadata = mpl_token_metadata::accounts::Metadata::safe_deserialize(&account.data).unwrap();
+    assert_eq!(metadata.mint, mint);
+    assert_eq!(metadata.update_authority, launch);
+    assert!(metadata.is_mutable);
+    assert_eq!(metadata.name.trim_end_matches('\0'), NAME);
+    assert_eq!(metadata.symbol.trim_end_matches('\0'), SYMBOL);
+    assert_eq!(metadata.uri.trim_end_matches('\0'), URI);
+}
+
+#[tokio::test]
+async fn test_create_launch_token_2022_metadata() {
+    let mut context = program_test().start_with_context().await;
+    let creator = context.payer.insecure_clone();
+
+    initialize(&mut context).await;
+    let (mint, launch) = create_launch(&mut context, &creator, anchor_spl::token_2022::ID, None).await;
+
+    let account = context.banks_client.get_account(mint).await.unwrap().expect("mint account");
+    assert_eq!(account.owner, anchor_spl::token_2022::ID);
+
+    let mint_state = StateWithExtensions::<Mint>::unpack(&account.data).unwrap();
+    assert!(mint_state.base.mint_authority.is_none());
+    assert!(mint_state.base.freeze_authority.is_none());
+
+    let launch_state = get_launch(&mut context, &launch).await;
+    assert!(launch_state.supply_finalized);
+
+    let metadata = mint_state.get_variable_len_extension::<TokenMetadata>().unwrap();
+    assert_eq!(metadata.mint, mint);
+    assert_eq!(Option::<Pubkey>::from(metadata.update_authority), Some(launch));
+    assert_eq!(metadata.name, NAME);
+    assert_eq!(metadata.symbol, SYMBOL);
+    assert_eq!(metadata.uri, URI);
+}
+
+#[tokio::test]
+async fn test_update_launch_metadata_token_2022() {
+    let mut context = program_test().start_with_context().await;
+    let creator = context.payer.insecure_clone();
+
+    initialize(&mut context).await;
+    let (mint, launch) = create_launch(&mut context, &creator, anchor_spl::token_2022::ID, None).await;
+
+    // A longer uri grows the mint, the creator pays the extra rent
+    let new_name = "Renamed Token";
+    let new_uri = "https://test.com/metadata/renamed-token-with-new-artwork.json";
+
+    update_launch_metadata(&mut context, &creator, &mint, &launch, anchor_spl::token_2022::ID, Some(new_name), Some(new_uri))
+        .await
+        .unwrap();
+
+    let account = context.banks_client.get_account(mint).await.unwrap().expect("mint account");
+    let mint_state = StateWithExtensions::<Mint>::unpack(&account.data).unwrap();
+    let metadata = mint_state.get_variable_len_extension::<TokenMetadata>().unwrap();
+    assert_eq!(metadata.name, new_name);
+    assert_eq!(metadata.symbol, SYMBOL);
+    assert_eq!(metadata.uri, new_uri);
+
+    let launch_state = get_launch(&mut context, &launch).await;
+    assert_eq!(launch_state.name, new_name);
+    assert_eq!(launch_state.uri, new_uri);
+}
+
+#[tokio::test]
+async fn test_update_launch_metadata_spl_token() {
+    let mut context = program_test().start_with_context().await;
+    let admin = context.payer.insecure_clone();
+
+    let community_pool = initialize(&mut context).await;
+    let creator = wallet(&mut context, 10 * LAMPORTS_PER_SOL).await;
+    let buyer = wallet(&mut context, 1_000 * LAMPORTS_PER_SOL).await;
+    let (mint, launch) = create_launch(&mut context, &creator, anchor_spl::token::ID, None).await;
+
+    let new_uri = "https://test.com/metadata/renamed-token-with-new-artwork.json";
+    update_launch_metadata(&mut context, &creator, &mint, &launch, anchor_spl::token::ID, None, Some(new_uri))
+        .await
+        .unwrap();
+
+    let (metadata, _) = mpl_token_metadata::accounts::Metadata::find_pda(&mint);
+    let account = context.banks_client.get_account(metadata).await.unwrap().expect("metadata account");
+    let metadata_state = mpl_token_metadata::accounts::Metadata::safe_deserialize(&account.data).unwrap();
+    assert_eq!(metadata_state.uri.trim_end_matches('\0'), new_uri);
+    assert_eq!(get_launch(&mut context, &launch).await.uri, new_uri);
+
+    // Migration locks the metadata for good
+    buy(&mut context, &buyer, &mint, &launch, &community_pool, anchor_spl::token::ID, 2 * FUND_RAISING).await.unwrap();
+    migrate(&mut context, &admin, &mint, &launch, anchor_spl::token::ID).await.unwrap();
+    assert!(get_launch(&mut context, &launch).await.status == LaunchStatus::Migrated);
+
+    let account = context.banks_client.get_account(metadata).await.unwrap().expect("metadata account");
+    assert!(!mpl_token_metadata::accounts::Metadata::safe_deserialize(&account.data).unwrap().is_mutable);
+    assert_error(
+        update_launch_metadata(&mut context, &creator, &mint, &launch, anchor_spl::token::ID, None, Some(URI)).await,
+        LaunchpadError::MetadataLocked,
+    );
+}
+
+#[tokio::test]
+async fn test_traded_launch_keeps_name_and_symbol() {
+    let mut context = program_test().start_with_context().await;
+    let creator = context.payer.insecure_clone();
+
+    let community_pool = initialize(&mut context).await;
+    let buyer = wallet(&mut context, 10 * LAMPORTS_PER_SOL).await;
+    let (mint, launch) = create_launch(&mut context, &creator, anchor_spl::token_2022::ID, None).await;
+
+    // A buy sold straight back still fixes the name, the uri can move
+    buy(&mut context, &buyer, &mint, &launch, &community_pool, anchor_spl::token_2022::ID, LAMPORTS_PER_SOL)
+        .await
+        .unwrap();
+    let bought = token_balance(&mut context, &buyer.pubkey(), &mint, anchor_spl::token_2022::ID).await;
+    sell(&mut context, &buyer, &mint, &launch, anchor_spl::token_2022::ID, bought).await.unwrap();
+    assert_eq!(get_launch(&mut context, &launch).await.tokens_sold, 0);
+
+    assert_error(
+        update_launch_metadata(&mut context, &creator, &mint, &launch, anchor_spl::token_2022::ID, Some("Renamed Token"), None).await,
+        LaunchpadError::LaunchAlreadyTraded,
+    );
+    update_launch_metadata(&mut context, &creator, &mint, &launch, anchor_spl::token_2022::ID, None, Some("https://test.com/new.json"))
+        .await
+        .unwrap();
+}
@@ -0,0 +1,108 @@
+//! Migrating completed launches through the program.
+//!
+//!     cargo test -p launchpad --test migrate
+
+mod common;
+
+use common::*;
+use launchpad::errors::LaunchpadError;
+use launchpad::state::{CreateLaunchParams, Launch, LaunchStatus};
+
+#[tokio::test]
+async fn test_migrate_after_overshooting_buy() {
+    for token_program in TOKEN_PROGRAMS {
+        let mut context = program_test().start_with_context().await;
+        let admin = context.payer.insecure_clone();
+
+        let community_pool = initialize(&mut context).await;
+        let creator = wallet(&mut context, 10 * LAMPORTS_PER_SOL).await;
+        let buyer = wallet(&mut context, 1_000 * LAMPORTS_PER_SOL).await;
+
+        let (mint, launch) = create_launch(&mut context, &creator, token_program, None).await;
+        assert_error(migrate(&mut context, &admin, &mint, &launch, token_program).await, LaunchpadError::GoalNotReached);
+
+        // One buy runs past the goal position to sell out the curve, where the pool needs the
+        // most tokens to open at the curve's price
+        buy(&mut context, &buyer, &mint, &launch, &community_pool, token_program, 2 * FUND_RAISING).await.unwrap();
+        let completed = get_launch(&mut context, &launch).await;
+        assert!(completed.status == LaunchStatus::Completed);
+        assert_eq!(completed.tokens_sold, SELL_AMOUNT);
+
+        let reserved = token_balance(&mut context, &launch, &mint, token_program).await;
+        assert_eq!(reserved, completed.migration_allocation(&[]).unwrap());
+        assert_eq!(completed.migration_token_amount(SELL_AMOUNT, &[]).unwrap(), reserved);
+
+        // Only the config authority migrates
+        assert_error(migrate(&mut context, &creator, &mint, &launch, token_program).await, LaunchpadError::InvalidAuthority);
+        let launch_lamports = balance(&mut context, &launch).await;
+        migrate(&mut context, &admin, &mint, &launch, token_program).await.unwrap();
+
+        // The pool gets every reserved token and all the raised SOL, none of it goes to the creator
+        let (pool_tokens, pool_sol) = completed.migration_pool(reserved, &[]).unwrap();
+        assert_eq!(pool_tokens, reserved);
+        assert_eq!(pool_sol, completed.sol_raised);
+
+        let migrated = get_launch(&mut context, &launch).await;
+        assert!(migrated.status == LaunchStatus::Migrated);
+        assert_eq!(migrated.creator_fee_earned, completed.creator_fee_earned);
+        assert_eq!(token_balance(&mut context, &migrated.pool_address, &mint, token_program).await, reserved);
+        assert_eq!(balance(&mut context, &migrated.pool_address).await, completed.sol_raised);
+        assert_eq!(balance(&mut context, &launch).await, launch_lamports - completed.sol_raised);
+        assert_eq!(token_balance(&mut context, &launch, &mint, token_program).await, 0);
+        assert_eq!(mint_supply(&mut context, &mint).await, SUPPLY);
+
+        assert_error(migrate(&mut context, &admin, &mint, &launch, token_program).await, LaunchpadError::AlreadyMigrated);
+    }
+}
+
+#[tokio::test]
+async fn test_migrate_burns_unsold_tokens() {
+    for token_program in TOKEN_PROGRAMS {
+        let mut context = program_test().start_with_context().await;
+        let admin = context.payer.insecure_clone();
+
+        let community_pool = initialize(&mut context).await;
+        let creator = wallet(&mut context, 10 * LAMPORTS_PER_SOL).await;
+        let buyer = wallet(&mut context, 1_000 * LAMPORTS_PER_SOL).await;
+
+        // Just past the goal, well short of selling out the curve
+        let (mint, launch) = create_launch(&mut context, &creator, token_program, None).await;
+        buy(&mut context, &buyer, &mint, &launch, &community_pool, token_program, FUND_RAISING * 102 / 100).await.unwrap();
+        let completed = get_launch(&mut context, &launch).await;
+        assert!(completed.status == LaunchStatus::Completed);
+        assert!(completed.tokens_sold < SELL_AMOUNT);
+
+        let launch_tokens = token_balance(&mut context, &launch, &mint, token_program).await;
+        let unsold = SELL_AMOUNT - completed.tokens_sold;
+        let (pool_tokens, pool_sol) = completed.migration_pool(launch_tokens - unsold, &[]).unwrap();
+        assert_eq!(pool_sol, completed.sol_raised);
+
+        migrate(&mut context, &admin, &mint, &launch, token_program).await.unwrap();
+
+        // The pool holds its tokens and SOL, the unsold curve tokens and anything else left are
+        // burned, the launch keeps only its rent and the creator's fees
+        let migrated = get_launch(&mut context, &launch).await;
+        assert_eq!(migrated.creator_fee_earned, completed.creator_fee_earned);
+        assert_eq!(token_balance(&mut context, &migrated.pool_address, &mint, token_program).await, pool_tokens);
+        assert_eq!(balance(&mut context, &migrated.pool_address).await, pool_sol);
+        let rent = context.banks_client.get_rent().await.unwrap().minimum_balance(Launch::LEN);
+        assert_eq!(balance(&mut context, &launch).await, rent + completed.creator_fee_earned);
+        assert_eq!(token_balance(&mut context, &launch, &mint, token_program).await, 0);
+        assert_eq!(mint_supply(&mut context, &mint).await, SUPPLY - (launch_tokens - pool_tokens));
+        assert!(launch_tokens - pool_tokens >= unsold);
+    }
+}
+
+#[tokio::test]
+async fn test_create_rejects_underfunded_pool() {
+    for token_program in TOKEN_PROGRAMS {
+        let mut context = program_test().start_with_context().await;
+        let creator = context.payer.insecure_clone();
+        initialize(&mut context).await;
+
+        // Sold out at 70%, a Linear curve would need more than the 30% left to pool its SOL
+        let params = CreateLaunchParams { total_sell_amount: SUPPLY / 10 * 7, ..launch_params(None) };
+        let result = try_create_launch_with(&mut context, &creator, token_program, params).await;
+        assert_error(result.map(|_| ()), LaunchpadError::PoolUnderfunded);
+    }
+}
@@ -0,0 +1,110 @@
+//! Allowlisted presales through the program.
+//!
+//!     cargo test -p launchpad --test presale
+
+mod common;
+
+use anchor_lang::solana_program::keccak::hashv;
+use common::*;
+use launchpad::errors::LaunchpadError;
+use launchpad::state::PresaleProof;
+use launchpad::utils::allowlist_leaf;
+use solana_sdk::clock::Clock;
+use solana_sdk::signature::Signer;
+
+#[tokio::test]
+async fn test_configure_presale_end_time() {
+    for token_program in TOKEN_PROGRAMS {
+        let mut context = program_test().start_with_context().await;
+        let creator = context.payer.insecure_clone();
+
+        initialize(&mut context).await;
+        let (_, launch) = create_launch(&mut context, &creator, token_program, None).await;
+
+        // A presale that would already be over by the time it is set does nothing
+        let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+        context.set_sysvar(&Clock { unix_timestamp: clock.unix_timestamp + 3_600, ..clock.clone() });
+        assert_error(
+            configure_presale(&mut context, &creator, &launch, [1; 32], clock.unix_timestamp + 1_800).await,
+            LaunchpadError::InvalidPresale,
+        );
+        assert_error(
+            configure_presale(&mut context, &creator, &launch, [1; 32], clock.unix_timestamp + 3_600).await,
+            LaunchpadError::InvalidPresale,
+        );
+
+        configure_presale(&mut context, &creator, &launch, [1; 32], clock.unix_timestamp + 7_200).await.unwrap();
+        let launch_state = get_launch(&mut context, &launch).await;
+        assert_eq!(launch_state.presale_merkle_root, [1; 32]);
+        assert_eq!(launch_state.presale_end_time, clock.unix_timestamp + 7_200);
+    }
+}
+
+/// Root of a two-leaf allowlist, hashing the pair in sorted order like the program
+fn merkle_root(leaf: [u8; 32], sibling: [u8; 32]) -> [u8; 32] {
+    if leaf <= sibling {
+        hashv(&[&leaf, &sibling]).to_bytes()
+    } else {
+        hashv(&[&sibling, &leaf]).to_bytes()
+    }
+}
+
+#[tokio::test]
+async fn test_presale_buys_need_allowlist_proof() {
+    for token_program in TOKEN_PROGRAMS {
+        let mut context = program_test().start_with_context().await;
+        let creator = context.payer.insecure_clone();
+
+        let community_pool = initialize(&mut context).await;
+        let capped = wallet(&mut context, 100 * LAMPORTS_PER_SOL).await;
+        let uncapped = wallet(&mut context, 100 * LAMPORTS_PER_SOL).await;
+        let outsider = wallet(&mut context, 100 * LAMPORTS_PER_SOL).await;
+
+        let (mint, launch) = create_launch(&mut context, &creator, token_program, None).await;
+
+        // One wallet may buy what 2 SOL gets from the start of the curve, the other is uncapped
+        let allocation = get_launch(&mut context, &launch).await
+            .calculate_tokens_for_sol(2 * LAMPORTS_PER_SOL, &[])
+            .unwrap();
+        let capped_leaf = allowlist_leaf(&capped.pubkey(), allocation);
+        let uncapped_leaf = allowlist_leaf(&uncapped.pubkey(), 0);
+        let capped_proof = PresaleProof { allocation, proof: vec![uncapped_leaf] };
+        let uncapped_proof = PresaleProof { allocation: 0, proof: vec![capped_leaf] };
+
+        let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+        let end_time = clock.unix_timestamp + 3_600;
+        configure_presale(&mut context, &creator, &launch, merkle_root(capped_leaf, uncapped_leaf), end_time)
+            .await
+            .unwrap();
+
+        let presale_buy = |buyer, sol_amount, proof| {
+            buy_instruction(buyer, &mint, &launch, &community_pool, token_program, sol_amount, proof)
+        };
+
+        // No proof, someone else's proof, or an inflated allocation are all turned away
+        let no_proof = presale_buy(&outsider, LAMPORTS_PER_SOL, None);
+        let borrowed = presale_buy(&outsider, LAMPORTS_PER_SOL, Some(capped_proof.clone()));
+        let inflated_proof = PresaleProof { allocation: 2 * allocation, ..capped_proof.clone() };
+        let inflated = presale_buy(&capped, LAMPORTS_PER_SOL, Some(inflated_proof));
+        assert_error(process(&mut context, &[no_proof], &[&outsider]).await, LaunchpadError::NotAllowlisted);
+        assert_error(process(&mut context, &[borrowed], &[&outsider]).await, LaunchpadError::NotAllowlisted);
+        assert_error(process(&mut context, &[inflated], &[&capped]).await, LaunchpadError::NotAllowlisted);
+
+        // The capped wallet buys up to its allocation across buys, and no further
+        let first = presale_buy(&capped, LAMPORTS_PER_SOL, Some(capped_proof.clone()));
+        process(&mut context, &[first], &[&capped]).await.unwrap();
+        let over = presale_buy(&capped, 2 * LAMPORTS_PER_SOL, Some(capped_proof.clone()));
+        assert_error(process(&mut context, &[over], &[&capped]).await, LaunchpadError::PresaleAllocationExceeded);
+        assert!(token_balance(&mut context, &capped.pubkey(), &mint, token_program).await <= allocation);
+
+        // An allocation of 0 means no cap
+        let large = presale_buy(&uncapped, 20 * LAMPORTS_PER_SOL, Some(uncapped_proof));
+        process(&mut context, &[large], &[&uncapped]).await.unwrap();
+        assert!(token_balance(&mut context, &uncapped.pubkey(), &mint, token_program).await > allocation);
+
+        // Once the presale ends anyone buys without a proof
+        context.set_sysvar(&Clock { unix_timestamp: end_time, ..clock });
+        buy(&mut context, &outsider, &mint, &launch, &community_pool, token_program, LAMPORTS_PER_SOL).await.unwrap();
+        buy(&mut context, &capped, &mint, &launch, &community_pool, token_program, 2 * LAMPORTS_PER_SOL).await.unwrap();
+    }
+}
@@ -0,0 +1,189 @@
+//! Cancelling launches and refunding holders through the program.
+//!
+//!     cargo test -p launchpad --test refund
+
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_2022::spl_token_2022;
+use common::*;
+use launchpad::errors::LaunchpadError;
+use launchpad::state::{LaunchLimits, LaunchStatus};
+use solana_sdk::clock::Clock;
+use solana_sdk::signature::Signer;
+
+#[tokio::test]
+async fn test_cancel_launch_permissions() {
+    for token_program in TOKEN_PROGRAMS {
+        let mut context = program_test().start_with_context().await;
+        let admin = context.payer.insecure_clone();
+
+        let community_pool = initialize(&mut context).await;
+        let creator = wallet(&mut context, 10 * LAMPORTS_PER_SOL).await;
+        let stranger = wallet(&mut context, LAMPORTS_PER_SOL).await;
+        let buyer = wallet(&mut context, 1_000 * LAMPORTS_PER_SOL).await;
+
+        // The creator can cancel an untraded launch, nobody else but the admin
+        let (_, launch) = create_launch(&mut context, &creator, token_program, None).await;
+        assert_error(cancel_launch(&mut context, &stranger, &launch).await, LaunchpadError::InvalidAuthority);
+        cancel_launch(&mut context, &creator, &launch).await.unwrap();
+        assert!(get_launch(&mut context, &launch).await.status == LaunchStatus::Cancelled);
+
+        // Cancelling is final
+        assert_error(cancel_launch(&mut context, &admin, &launch).await, LaunchpadError::LaunchNotActive);
+
+        // Once traded only the admin can cancel an Active launch
+        let (mint, launch) = create_launch(&mut context, &creator, token_program, None).await;
+        buy(&mut context, &buyer, &mint, &launch, &community_pool, token_program, LAMPORTS_PER_SOL).await.unwrap();
+        assert_error(cancel_launch(&mut context, &creator, &launch).await, LaunchpadError::LaunchAlreadyTraded);
+        assert_error(cancel_launch(&mut context, &stranger, &launch).await, LaunchpadError::InvalidAuthority);
+        cancel_launch(&mut context, &admin, &launch).await.unwrap();
+        assert!(get_launch(&mut context, &launch).await.status == LaunchStatus::Cancelled);
+
+        // Selling everything back leaves nothing sold, but the launch still counts as traded
+        let (mint, launch) = create_launch(&mut context, &creator, token_program, None).await;
+        buy(&mut context, &buyer, &mint, &launch, &community_pool, token_program, LAMPORTS_PER_SOL).await.unwrap();
+        let bought = token_balance(&mut context, &buyer.pubkey(), &mint, token_program).await;
+        sell(&mut context, &buyer, &mint, &launch, token_program, bought).await.unwrap();
+        assert_eq!(get_launch(&mut context, &launch).await.tokens_sold, 0);
+        assert_error(cancel_launch(&mut context, &creator, &launch).await, LaunchpadError::LaunchAlreadyTraded);
+
+        // and a Completed one that has not migrated yet
+        let (mint, launch) = create_launch(&mut context, &creator, token_program, None).await;
+        buy(&mut context, &buyer, &mint, &launch, &community_pool, token_program, 2 * FUND_RAISING).await.unwrap();
+        assert!(get_launch(&mut context, &launch).await.status == LaunchStatus::Completed);
+        assert_error(cancel_launch(&mut context, &creator, &launch).await, LaunchpadError::LaunchAlreadyTraded);
+        cancel_launch(&mut context, &admin, &launch).await.unwrap();
+        assert!(get_launch(&mut context, &launch).await.status == LaunchStatus::Cancelled);
+    }
+}
+
+#[tokio::test]
+async fn test_cancelled_refund_rejects_dust() {
+    for token_program in TOKEN_PROGRAMS {
+        let mut context = program_test().start_with_context().await;
+        let admin = context.payer.insecure_clone();
+
+        let community_pool = initialize(&mut context).await;
+        let creator = wallet(&mut context, 10 * LAMPORTS_PER_SOL).await;
+        let holder = wallet(&mut context, 10 * LAMPORTS_PER_SOL).await;
+        let other = wallet(&mut context, 10 * LAMPORTS_PER_SOL).await;
+
+        let (mint, launch) = create_launch(&mut context, &creator, token_program, None).await;
+        buy(&mut context, &holder, &mint, &launch, &community_pool, token_program, LAMPORTS_PER_SOL).await.unwrap();
+        buy(&mut context, &other, &mint, &launch, &community_pool, token_program, LAMPORTS_PER_SOL).await.unwrap();
+        cancel_launch(&mut context, &admin, &launch).await.unwrap();
+
+        // Keep a single base unit, whose share of the reserve rounds down to nothing
+        let held = token_balance(&mut context, &holder.pubkey(), &mint, token_program).await;
+        let transfer = spl_token_2022::instruction::transfer_checked(
+            &token_program,
+            &get_associated_token_address_with_program_id(&holder.pubkey(), &mint, &token_program),
+            &mint,
+            &get_associated_token_address_with_program_id(&other.pubkey(), &mint, &token_program),
+            &holder.pubkey(),
+            &[],
+            held - 1,
+            LaunchLimits::DEFAULT.min_decimals,
+        )
+        .unwrap();
+        process(&mut context, &[transfer], &[&holder]).await.unwrap();
+
+        assert_error(
+            claim_refund(&mut context, &holder, &mint, &launch, token_program).await,
+            LaunchpadError::AmountTooSmall,
+        );
+        assert_eq!(token_balance(&mut context, &holder.pubkey(), &mint, token_program).await, 1);
+
+        // A real position still gets its share
+        let other_before = balance(&mut context, &other.pubkey()).await;
+        claim_refund(&mut context, &other, &mint, &launch, token_program).await.unwrap();
+        assert!(balance(&mut context, &other.pubkey()).await > other_before);
+        assert_eq!(get_position(&mut context, &launch, &other.pubkey()).await.net_tokens(), 0);
+    }
+}
+
+#[tokio::test]
+async fn test_failed_refunds_split_short_reserve() {
+    for token_program in TOKEN_PROGRAMS {
+        let mut context = program_test().start_with_context().await;
+
+        let community_pool = initialize(&mut context).await;
+        let creator = wallet(&mut context, 10 * LAMPORTS_PER_SOL).await;
+        let early = wallet(&mut context, 100 * LAMPORTS_PER_SOL).await;
+        let late = wallet(&mut context, 100 * LAMPORTS_PER_SOL).await;
+
+        let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+        let deadline = clock.unix_timestamp + 3_600;
+        let (mint, launch) = create_launch(&mut context, &creator, token_program, Some(deadline)).await;
+
+        // The early buyer sells most of its tokens into the late buy for more than it paid,
+        // then buys back in, so the reserve ends up short of the net SOL outstanding
+        buy(&mut context, &early, &mint, &launch, &community_pool, token_program, 10 * LAMPORTS_PER_SOL).await.unwrap();
+        buy(&mut context, &late, &mint, &launch, &community_pool, token_program, 50 * LAMPORTS_PER_SOL).await.unwrap();
+        let held = token_balance(&mut context, &early.pubkey(), &mint, token_program).await;
+        sell(&mut context, &early, &mint, &launch, token_program, held / 10 * 8).await.unwrap();
+        assert_eq!(get_position(&mut context, &launch, &early.pubkey()).await.sol_in_curve, 0);
+        buy(&mut context, &early, &mint, &launch, &community_pool, token_program, 5 * LAMPORTS_PER_SOL).await.unwrap();
+
+        let launch_state = get_launch(&mut context, &launch).await;
+        let early_net = get_position(&mut context, &launch, &early.pubkey()).await.sol_in_curve;
+        let late_net = get_position(&mut context, &launch, &late.pubkey()).await.sol_in_curve;
+        assert_eq!(launch_state.sol_in_curve, early_net + late_net);
+        assert!(launch_state.sol_raised < launch_state.sol_in_curve);
+
+        context.set_sysvar(&Clock { unix_timestamp: deadline, ..clock });
+
+        // Both get the same fraction of their net SOL, whoever claims first
+        let reserve = launch_state.sol_raised as u128;
+        let outstanding = launch_state.sol_in_curve as u128;
+        for (holder, net_sol) in [(&early, early_net), (&late, late_net)] {
+            let before = balance(&mut context, &holder.pubkey()).await;
+            claim_refund(&mut context, holder, &mint, &launch, token_program).await.unwrap();
+            let refund = (balance(&mut context, &holder.pubkey()).await - before) as u128;
+
+            let pro_rata = reserve * net_sol as u128 / outstanding;
+            assert!(refund == pro_rata || refund == pro_rata + 1, "{} vs {}", refund, pro_rata);
+            assert_eq!(token_balance(&mut context, &holder.pubkey(), &mint, token_program).await, 0);
+        }
+
+        let launch_state = get_launch(&mut context, &launch).await;
+        assert!(launch_state.status == LaunchStatus::Failed);
+        assert_eq!(launch_state.sol_raised, 0);
+        assert_eq!(launch_state.sol_in_curve, 0);
+    }
+}
+
+#[tokio::test]
+async fn test_expired_launch_fails_instead_of_cancelling() {
+    for token_program in TOKEN_PROGRAMS {
+        let mut context = program_test().start_with_context().await;
+        let admin = context.payer.insecure_clone();
+
+        let community_pool = initialize(&mut context).await;
+        let creator = wallet(&mut context, 10 * LAMPORTS_PER_SOL).await;
+        let buyer = wallet(&mut context, 100 * LAMPORTS_PER_SOL).await;
+
+        let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+        let deadline = clock.unix_timestamp + 3_600;
+        let (mint, launch) = create_launch(&mut context, &creator, token_program, Some(deadline)).await;
+        buy(&mut context, &buyer, &mint, &launch, &community_pool, token_program, 10 * LAMPORTS_PER_SOL).await.unwrap();
+
+        // Nobody can fail a launch still inside its deadline
+        assert_error(fail_launch(&mut context, &launch).await, LaunchpadError::LaunchNotExpired);
+
+        // Past the deadline even the admin can't switch it to the cancelled refund basis
+        context.set_sysvar(&Clock { unix_timestamp: deadline, ..clock });
+        assert_error(cancel_launch(&mut context, &admin, &launch).await, LaunchpadError::DeadlinePassed);
+
+        // Anyone can mark it Failed without signing, once
+        fail_launch(&mut context, &launch).await.unwrap();
+        assert!(get_launch(&mut context, &launch).await.status == LaunchStatus::Failed);
+        assert_error(fail_launch(&mut context, &launch).await, LaunchpadError::LaunchNotExpired);
+        assert_error(cancel_launch(&mut context, &admin, &launch).await, LaunchpadError::LaunchNotActive);
+
+        // and holders refund on the failed basis
+        claim_refund(&mut context, &buyer, &mint, &launch, token_program).await.unwrap();
+        assert_eq!(get_launch(&mut context, &launch).await.sol_in_curve, 0);
+    }
+}
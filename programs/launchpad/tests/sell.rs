@@ -0,0 +1,72 @@
+//! Selling back to the curve through the program.
+//!
+//!     cargo test -p launchpad --test sell
+
+mod common;
+
+use common::*;
+use launchpad::errors::LaunchpadError;
+use solana_sdk::signature::Signer;
+
+#[tokio::test]
+async fn test_sell_exact_out() {
+    for token_program in TOKEN_PROGRAMS {
+        let mut context = program_test().start_with_context().await;
+        let creator = context.payer.insecure_clone();
+
+        let community_pool = initialize(&mut context).await;
+        let seller = wallet(&mut context, 100 * LAMPORTS_PER_SOL).await;
+        let other = wallet(&mut context, 100 * LAMPORTS_PER_SOL).await;
+        let (mint, launch) = create_launch(&mut context, &creator, token_program, None).await;
+
+        // A later buy raises the price the seller's tokens are worth
+        buy(&mut context, &seller, &mint, &launch, &community_pool, token_program, 10 * LAMPORTS_PER_SOL).await.unwrap();
+        buy(&mut context, &other, &mint, &launch, &community_pool, token_program, 20 * LAMPORTS_PER_SOL).await.unwrap();
+
+        let before = get_launch(&mut context, &launch).await;
+        let position_before = get_position(&mut context, &launch, &seller.pubkey()).await;
+        let sol_amount = LAMPORTS_PER_SOL;
+        let tokens_in = before.calculate_tokens_for_sol_out(sol_amount, &[]).unwrap();
+
+        assert_error(
+            sell_exact_out(&mut context, &seller, &mint, &launch, token_program, sol_amount, tokens_in - 1).await,
+            LaunchpadError::SlippageExceeded,
+        );
+
+        let held = token_balance(&mut context, &seller.pubkey(), &mint, token_program).await;
+        let seller_before = balance(&mut context, &seller.pubkey()).await;
+        sell_exact_out(&mut context, &seller, &mint, &launch, token_program, sol_amount, tokens_in).await.unwrap();
+
+        // Exactly sol_amount paid out, sells carry no fee, for the fewest tokens that cover it
+        assert_eq!(balance(&mut context, &seller.pubkey()).await - seller_before, sol_amount);
+        assert_eq!(held - token_balance(&mut context, &seller.pubkey(), &mint, token_program).await, tokens_in);
+
+        let after = get_launch(&mut context, &launch).await;
+        assert_eq!(after.tokens_sold, before.tokens_sold - tokens_in);
+        assert_eq!(after.sol_raised, before.sol_raised - sol_amount);
+        assert_eq!(after.sol_in_curve, before.sol_in_curve - sol_amount);
+        assert_eq!(after.creator_fee_earned, before.creator_fee_earned);
+
+        let position = get_position(&mut context, &launch, &seller.pubkey()).await;
+        assert_eq!(position.tokens_sold, tokens_in);
+        assert_eq!(position.sol_received, sol_amount);
+        assert_eq!(position.sol_in_curve, position_before.sol_in_curve - sol_amount);
+
+        // Payouts never reach past the SOL raised on the curve
+        assert_error(
+            sell_exact_out(&mut context, &seller, &mint, &launch, token_program, after.sol_raised + 1, u64::MAX).await,
+            LaunchpadError::InsufficientSOL,
+        );
+
+        // Taking out more than the seller put in clears their share of the curve SOL and no one else's
+        let other_position = get_position(&mut context, &launch, &other.pubkey()).await;
+        let profit_sale = position.sol_in_curve + LAMPORTS_PER_SOL;
+        sell_exact_out(&mut context, &seller, &mint, &launch, token_program, profit_sale, u64::MAX).await.unwrap();
+
+        let position = get_position(&mut context, &launch, &seller.pubkey()).await;
+        assert_eq!(position.sol_in_curve, 0);
+        assert_eq!(position.sol_received, sol_amount + profit_sale);
+        let after = get_launch(&mut context, &launch).await;
+        assert_eq!(after.sol_in_curve, other_position.sol_in_curve);
+    }
+}
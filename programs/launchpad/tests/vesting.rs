@@ -0,0 +1,203 @@
+//! Releasing creator vesting and allocation buckets after migration.
+//!
+//!     cargo test -p launchpad --test vesting
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use launchpad::errors::LaunchpadError;
+use launchpad::state::{AllocationKind, AllocationParams, CreateLaunchParams, CurveType};
+use launchpad::utils::vested_amount;
+use solana_sdk::clock::Clock;
+use solana_sdk::signature::Signer;
+
+const DAY: i64 = 86_400;
+
+#[tokio::test]
+async fn test_vesting_and_allocation_claims() {
+    for token_program in TOKEN_PROGRAMS {
+        let mut context = program_test().start_with_context().await;
+        let admin = context.payer.insecure_clone();
+
+        let community_pool = initialize(&mut context).await;
+        let creator = wallet(&mut context, 10 * LAMPORTS_PER_SOL).await;
+        let buyer = wallet(&mut context, 1_000 * LAMPORTS_PER_SOL).await;
+        let cranker = wallet(&mut context, LAMPORTS_PER_SOL).await;
+        let team = wallet(&mut context, LAMPORTS_PER_SOL).await;
+        let airdrop = wallet(&mut context, LAMPORTS_PER_SOL).await;
+
+        // An Exponential curve leaves part of the supply past the migration pool to vest: a team
+        // bucket on a cliff, an airdrop bucket unlocked at migration, and the creator's rest
+        let team_amount = SUPPLY / 100;
+        let airdrop_amount = SUPPLY / 200;
+        let params = CreateLaunchParams {
+            curve_type: CurveType::Exponential,
+            cliff_period: DAY,
+            unlock_period: 10 * DAY,
+            allocations: vec![
+                AllocationParams {
+                    kind: AllocationKind::Team,
+                    beneficiary: team.pubkey(),
+                    amount: team_amount,
+                    cliff_period: 30 * DAY,
+                    unlock_period: 100 * DAY,
+                },
+                AllocationParams {
+                    kind: AllocationKind::Airdrop,
+                    beneficiary: airdrop.pubkey(),
+                    amount: airdrop_amount,
+                    cliff_period: 0,
+                    unlock_period: 0,
+                },
+            ],
+            ..launch_params(None)
+        };
+        let (mint, launch) = create_launch_with(&mut context, &creator, token_program, params).await;
+        let vesting_amount = get_launch(&mut context, &launch).await.vesting_amount;
+        assert!(vesting_amount > 0);
+        // The creator's share and each bucket are held in vaults of their own
+        let vault = vesting_vault_address(&launch);
+        let team_vault = allocation_vault_address(&launch, 0);
+        let airdrop_vault = allocation_vault_address(&launch, 1);
+        assert_eq!(token_account_balance(&mut context, &vault).await, vesting_amount);
+        assert_eq!(token_account_balance(&mut context, &team_vault).await, team_amount);
+        assert_eq!(token_account_balance(&mut context, &airdrop_vault).await, airdrop_amount);
+
+        // Nothing releases before migration
+        assert_error(claim_vested_tokens(&mut context, &creator, &mint, &launch, token_program).await, LaunchpadError::VestingNotEnded);
+        assert_error(
+            claim_allocation(&mut context, &cranker, &mint, &launch, token_program, &airdrop.pubkey(), 1).await,
+            LaunchpadError::VestingNotEnded,
+        );
+
+        buy(&mut context, &buyer, &mint, &launch, &community_pool, token_program, 2 * FUND_RAISING).await.unwrap();
+        migrate(&mut context, &admin, &mint, &launch, token_program).await.unwrap();
+        let migrate_time = get_launch(&mut context, &launch).await.migrate_time;
+
+        // The airdrop bucket is released in full by anyone, once
+        claim_allocation(&mut context, &cranker, &mint, &launch, token_program, &airdrop.pubkey(), 1).await.unwrap();
+        assert_eq!(token_balance(&mut context, &airdrop.pubkey(), &mint, token_program).await, airdrop_amount);
+        assert_error(
+            claim_allocation(&mut context, &cranker, &mint, &launch, token_program, &airdrop.pubkey(), 1).await,
+            LaunchpadError::VestingNotEnded,
+        );
+
+        // Buckets pay out only to their own beneficiary, and ones never created have no vault
+        assert_error(
+            claim_allocation(&mut context, &cranker, &mint, &launch, token_program, &cranker.pubkey(), 0).await,
+            LaunchpadError::AllocationNotFound,
+        );
+        assert_error(
+            claim_allocation(&mut context, &cranker, &mint, &launch, token_program, &team.pubkey(), 2).await,
+            ErrorCode::AccountNotInitialized,
+        );
+
+        // Cliffs hold back the creator and the team, and only the creator claims its vesting
+        assert_error(claim_vested_tokens(&mut context, &creator, &mint, &launch, token_program).await, LaunchpadError::VestingNotEnded);
+        assert_error(
+            claim_allocation(&mut context, &cranker, &mint, &launch, token_program, &team.pubkey(), 0).await,
+            LaunchpadError::VestingNotEnded,
+        );
+        assert_error(claim_vested_tokens(&mut context, &cranker, &mint, &launch, token_program).await, ErrorCode::ConstraintHasOne);
+
+        // Halfway through its unlock the creator gets half, and no more until later
+        let clock: Clock = context.banks_client.get_sysvar().await.unwrap();
+        let now = migrate_time + 6 * DAY;
+        context.set_sysvar(&Clock { unix_timestamp: now, ..clock.clone() });
+
+        claim_vested_tokens(&mut context, &creator, &mint, &launch, token_program).await.unwrap();
+        let creator_vested = vested_amount(vesting_amount, migrate_time, DAY, 10 * DAY, now);
+        assert_eq!(creator_vested, vesting_amount / 2);
+        assert_eq!(token_balance(&mut context, &creator.pubkey(), &mint, token_program).await, creator_vested);
+        assert_error(claim_vested_tokens(&mut context, &creator, &mint, &launch, token_program).await, LaunchpadError::VestingNotEnded);
+
+        // Partway through its own unlock the team bucket gets its linear share
+        let now = migrate_time + 31 * DAY;
+        context.set_sysvar(&Clock { unix_timestamp: now, ..clock.clone() });
+
+        claim_allocation(&mut context, &cranker, &mint, &launch, token_program, &team.pubkey(), 0).await.unwrap();
+        let team_vested = vested_amount(team_amount, migrate_time, 30 * DAY, 100 * DAY, now);
+        assert!(team_vested > 0 && team_vested < team_amount);
+        assert_eq!(token_balance(&mut context, &team.pubkey(), &mint, token_program).await, team_vested);
+        assert_error(
+            claim_allocation(&mut context, &cranker, &mint, &launch, token_program, &team.pubkey(), 0).await,
+            LaunchpadError::VestingNotEnded,
+        );
+
+        // After the unlocks everything is out and the vaults are empty
+        context.set_sysvar(&Clock { unix_timestamp: migrate_time + 130 * DAY, ..clock });
+        claim_vested_tokens(&mut context, &creator, &mint, &launch, token_program).await.unwrap();
+        claim_allocation(&mut context, &cranker, &mint, &launch, token_program, &team.pubkey(), 0).await.unwrap();
+        assert_eq!(token_balance(&mut context, &creator.pubkey(), &mint, token_program).await, vesting_amount);
+        assert_eq!(token_balance(&mut context, &team.pubkey(), &mint, token_program).await, team_amount);
+        for vault in [vault, team_vault, airdrop_vault] {
+            assert_eq!(token_account_balance(&mut context, &vault).await, 0);
+        }
+
+        assert_error(claim_vested_tokens(&mut context, &creator, &mint, &launch, token_program).await, LaunchpadError::VestingNotEnded);
+        assert_error(
+            claim_allocation(&mut context, &cranker, &mint, &launch, token_program, &team.pubkey(), 0).await,
+            LaunchpadError::VestingNotEnded,
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_burn_vesting_of_unmigrated_launch() {
+    for token_program in TOKEN_PROGRAMS {
+        let mut context = program_test().start_with_context().await;
+        let admin = context.payer.insecure_clone();
+
+        let community_pool = initialize(&mut context).await;
+        let creator = wallet(&mut context, 10 * LAMPORTS_PER_SOL).await;
+        let buyer = wallet(&mut context, 100 * LAMPORTS_PER_SOL).await;
+
+        let params = CreateLaunchParams {
+            curve_type: CurveType::Exponential,
+            allocations: vec![AllocationParams {
+                kind: AllocationKind::Team,
+                beneficiary: creator.pubkey(),
+                amount: SUPPLY / 100,
+                cliff_period: 0,
+                unlock_period: 0,
+            }],
+            ..launch_params(None)
+        };
+        let (mint, launch) = create_launch_with(&mut context, &creator, token_program, params).await;
+        buy(&mut context, &buyer, &mint, &launch, &community_pool, token_program, LAMPORTS_PER_SOL).await.unwrap();
+
+        // Vesting of a launch that can still migrate stays put
+        assert_error(burn_vesting_tokens(&mut context, &mint, &launch, token_program).await, LaunchpadError::LaunchNotCancelled);
+
+        assert_error(
+            burn_allocation_tokens(&mut context, &mint, &launch, token_program, 0).await,
+            LaunchpadError::LaunchNotCancelled,
+        );
+
+        // Once cancelled anyone can burn the vaults, taking the tokens out of supply
+        cancel_launch(&mut context, &admin, &launch).await.unwrap();
+        let vault = vesting_vault_address(&launch);
+        let vaulted = token_account_balance(&mut context, &vault).await;
+        assert!(vaulted > 0);
+        let team_vault = allocation_vault_address(&launch, 0);
+        assert_eq!(token_account_balance(&mut context, &team_vault).await, SUPPLY / 100);
+
+        burn_vesting_tokens(&mut context, &mint, &launch, token_program).await.unwrap();
+        assert_eq!(token_account_balance(&mut context, &vault).await, 0);
+        assert_eq!(mint_supply(&mut context, &mint).await, SUPPLY - vaulted);
+        burn_allocation_tokens(&mut context, &mint, &launch, token_program, 0).await.unwrap();
+        assert_eq!(token_account_balance(&mut context, &team_vault).await, 0);
+        assert_eq!(mint_supply(&mut context, &mint).await, SUPPLY - vaulted - SUPPLY / 100);
+
+        assert_error(burn_vesting_tokens(&mut context, &mint, &launch, token_program).await, LaunchpadError::VestingVaultEmpty);
+        assert_error(
+            burn_allocation_tokens(&mut context, &mint, &launch, token_program, 0).await,
+            LaunchpadError::VestingVaultEmpty,
+        );
+        assert_error(
+            claim_allocation(&mut context, &creator, &mint, &launch, token_program, &creator.pubkey(), 0).await,
+            LaunchpadError::VestingNotEnded,
+        );
+    }
+}
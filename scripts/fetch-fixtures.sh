@@ -0,0 +1,12 @@
+#!/usr/bin/env bash
+# Dump the mainnet programs the tests load into programs/launchpad/tests/fixtures,
+# skipping any already there
+set -euo pipefail
+
+FIXTURES="$(cd "$(dirname "$0")/.." && pwd)/programs/launchpad/tests/fixtures"
+mkdir -p "$FIXTURES"
+
+# Token Metadata, which SPL Token launches create their metadata with
+if [ ! -f "$FIXTURES/mpl_token_metadata.so" ]; then
+  solana program dump -u m metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s "$FIXTURES/mpl_token_metadata.so"
+fi
@@ -2,9 +2,17 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { Launchpad } from "../target/types/launchpad";
 import { PublicKey, Keypair, SystemProgram, SYSVAR_RENT_PUBKEY } from "@solana/web3.js";
-import { TOKEN_PROGRAM_ID, ASSOCIATED_TOKEN_PROGRAM_ID, getAssociatedTokenAddress } from "@solana/spl-token";
+import {
+  TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  getAssociatedTokenAddress,
+  getTokenMetadata,
+} from "@solana/spl-token";
 import { assert } from "chai";
 
+const TOKEN_METADATA_PROGRAM_ID = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
 describe("Launchpad", () => {
   const provider = anchor.AnchorProvider.env();
   anchor.setProvider(provider);
@@ -25,10 +33,92 @@ describe("Launchpad", () => {
     communityPool = Keypair.generate();
   });
 
+  const airdrop = async (to: PublicKey) => {
+    const signature = await provider.connection.requestAirdrop(
+      to,
+      2 * anchor.web3.LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(signature);
+  };
+
+  // Expect `rpc` to fail with the program or Anchor error `code`
+  const expectError = async (rpc: Promise<string>, code: string) => {
+    try {
+      await rpc;
+    } catch (error: any) {
+      assert.equal(error.error?.errorCode?.code, code);
+      return;
+    }
+    assert.fail(`expected ${code}`);
+  };
+
+  const createToken2022Launch = async (name: string, symbol: string, uri: string) => {
+    const creator = Keypair.generate();
+    await airdrop(creator.publicKey);
+
+    const mint = Keypair.generate();
+
+    const [launchPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from("launch"), mint.publicKey.toBuffer()],
+      program.programId
+    );
+
+    const launchTokenAccount = await getAssociatedTokenAddress(
+      mint.publicKey,
+      launchPDA,
+      true,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    const [vestingVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vesting"), launchPDA.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .createLaunch({
+        name,
+        symbol,
+        uri,
+        decimals: 9,
+        supply: new anchor.BN(1_000_000_000_000_000),
+        totalSellAmount: new anchor.BN(520_000_000_000_000),
+        totalFundRaising: new anchor.BN(85_000_000_000),
+        curveType: { linear: {} },
+        curvePoints: [],
+        migrateType: { cpmm: {} },
+        cliffPeriod: new anchor.BN(0),
+        unlockPeriod: new anchor.BN(0),
+        startTime: null,
+        deadline: null,
+        allocations: [],
+      })
+      .accounts({
+        launch: launchPDA,
+        mint: mint.publicKey,
+        launchTokenAccount,
+        vestingVault,
+        piecewiseCurve: null,
+        allocationTable: null,
+        config: configPDA,
+        creator: creator.publicKey,
+        metadata: null,
+        tokenMetadataProgram: null,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .signers([creator, mint])
+      .rpc();
+
+    return { creator, mint, launchPDA };
+  };
+
   it("Initializes the launchpad", async () => {
     try {
       await program.methods
-        .initialize(100) // 1% fee
+        .initialize(100, 10) // 1% fee, 0.1% referral fee
         .accounts({
           config: configPDA,
           authority: authority.publicKey,
@@ -40,6 +130,8 @@ describe("Launchpad", () => {
       const config = await program.account.launchpadConfig.fetch(configPDA);
       assert.equal(config.feePercentage, 100);
       assert.equal(config.referralFee, 10);
+      assert.equal(config.limits.minSellBps, 5100);
+      assert.equal(config.limits.maxSellBps, 8000);
       console.log("✓ Launchpad initialized successfully");
     } catch (error) {
       console.error("Error initializing launchpad:", error);
@@ -47,6 +139,55 @@ describe("Launchpad", () => {
     }
   });
 
+  it("Updates config and hands over authority in two steps", async () => {
+    const newAuthority = Keypair.generate();
+    const newCommunityPool = Keypair.generate();
+
+    await program.methods
+      .updateConfig(150, newCommunityPool.publicKey, null)
+      .accounts({ config: configPDA, authority: authority.publicKey })
+      .rpc();
+
+    let config = await program.account.launchpadConfig.fetch(configPDA);
+    assert.equal(config.feePercentage, 150);
+    assert.ok(config.communityPool.equals(newCommunityPool.publicKey));
+    assert.equal(config.referralFee, 10);
+
+    await program.methods
+      .proposeAuthority(newAuthority.publicKey)
+      .accounts({ config: configPDA, authority: authority.publicKey })
+      .rpc();
+
+    config = await program.account.launchpadConfig.fetch(configPDA);
+    assert.ok(config.authority.equals(authority.publicKey));
+    assert.ok(config.pendingAuthority.equals(newAuthority.publicKey));
+
+    await program.methods
+      .acceptAuthority()
+      .accounts({ config: configPDA, newAuthority: newAuthority.publicKey })
+      .signers([newAuthority])
+      .rpc();
+
+    config = await program.account.launchpadConfig.fetch(configPDA);
+    assert.ok(config.authority.equals(newAuthority.publicKey));
+
+    // Hand it back and restore the settings the remaining tests expect
+    await program.methods
+      .proposeAuthority(authority.publicKey)
+      .accounts({ config: configPDA, authority: newAuthority.publicKey })
+      .signers([newAuthority])
+      .rpc();
+    await program.methods
+      .acceptAuthority()
+      .accounts({ config: configPDA, newAuthority: authority.publicKey })
+      .rpc();
+    await program.methods
+      .updateConfig(100, communityPool.publicKey, null)
+      .accounts({ config: configPDA, authority: authority.publicKey })
+      .rpc();
+    console.log("✓ Config updated and authority transferred");
+  });
+
   it("Creates a token launch", async () => {
     const creator = Keypair.generate();
 
@@ -61,56 +202,75 @@ describe("Launchpad", () => {
     const symbol = "TEST";
     const uri = "https://test.com/metadata.json";
     const supply = new anchor.BN(1_000_000_000_000_000); // 1 billion with 9 decimals
-    const totalSellAmount = new anchor.BN(700_000_000_000_000); // 70%
+    const totalSellAmount = new anchor.BN(520_000_000_000_000); // 52%
     const totalFundRaising = new anchor.BN(85_000_000_000); // 85 SOL
 
-    const [mintPDA] = PublicKey.findProgramAddressSync(
-      [Buffer.from("mint"), creator.publicKey.toBuffer(), Buffer.from(name)],
-      program.programId
-    );
+    const mint = Keypair.generate();
 
     const [launchPDA] = PublicKey.findProgramAddressSync(
-      [Buffer.from("launch"), mintPDA.toBuffer()],
+      [Buffer.from("launch"), mint.publicKey.toBuffer()],
       program.programId
     );
 
     const launchTokenAccount = await getAssociatedTokenAddress(
-      mintPDA,
+      mint.publicKey,
       launchPDA,
       true
     );
 
+    const [metadata] = PublicKey.findProgramAddressSync(
+      [Buffer.from("metadata"), TOKEN_METADATA_PROGRAM_ID.toBuffer(), mint.publicKey.toBuffer()],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+
+    const [vestingVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vesting"), launchPDA.toBuffer()],
+      program.programId
+    );
+
     try {
       await program.methods
-        .createLaunch(
+        .createLaunch({
           name,
           symbol,
           uri,
+          decimals: 9,
           supply,
           totalSellAmount,
           totalFundRaising,
-          { linear: {} },
-          { cpmm: {} },
-          new anchor.BN(0),
-          new anchor.BN(0)
-        )
+          curveType: { linear: {} },
+          curvePoints: [],
+          migrateType: { cpmm: {} },
+          cliffPeriod: new anchor.BN(0),
+          unlockPeriod: new anchor.BN(0),
+          startTime: null,
+          deadline: null,
+          allocations: [],
+        })
         .accounts({
           launch: launchPDA,
-          mint: mintPDA,
+          mint: mint.publicKey,
           launchTokenAccount,
+          vestingVault,
+          piecewiseCurve: null,
+          allocationTable: null,
+          config: configPDA,
           creator: creator.publicKey,
+          metadata,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
           tokenProgram: TOKEN_PROGRAM_ID,
           associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
           rent: SYSVAR_RENT_PUBKEY,
         })
-        .signers([creator])
+        .signers([creator, mint])
         .rpc();
 
       const launch = await program.account.launch.fetch(launchPDA);
       assert.equal(launch.name, name);
       assert.equal(launch.symbol, symbol);
       assert.equal(launch.totalSupply.toString(), supply.toString());
+      assert.isTrue(launch.supplyFinalized);
       console.log("✓ Token launch created successfully");
     } catch (error) {
       console.error("Error creating launch:", error);
@@ -118,6 +278,88 @@ describe("Launchpad", () => {
     }
   });
 
+  it("Creates a Token-2022 launch with on-mint metadata", async () => {
+    const name = "Test Token 2022";
+    const symbol = "T22";
+    const uri = "https://test.com/metadata-2022.json";
+
+    const { mint, launchPDA } = await createToken2022Launch(name, symbol, uri);
+
+    const metadata = await getTokenMetadata(provider.connection, mint.publicKey);
+    assert.equal(metadata.name, name);
+    assert.equal(metadata.symbol, symbol);
+    assert.equal(metadata.uri, uri);
+    assert.ok(metadata.updateAuthority.equals(launchPDA));
+    console.log("✓ Token-2022 launch created with on-mint metadata");
+  });
+
+  it("Transfers the creator role in two steps", async () => {
+    const { creator, mint, launchPDA } = await createToken2022Launch(
+      "Creator Token",
+      "CRT",
+      "https://test.com/metadata-creator.json"
+    );
+    const newCreator = Keypair.generate();
+    const stranger = Keypair.generate();
+    // The new creator pays for the mint to grow with the longer uri
+    await airdrop(newCreator.publicKey);
+
+    const updateUri = (signer: Keypair, uri: string) =>
+      program.methods
+        .updateLaunchMetadata(null, null, uri)
+        .accounts({
+          launch: launchPDA,
+          mint: mint.publicKey,
+          config: configPDA,
+          creator: signer.publicKey,
+          metadata: null,
+          tokenMetadataProgram: null,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([signer])
+        .rpc();
+
+    await program.methods
+      .proposeCreator(newCreator.publicKey)
+      .accounts({ launch: launchPDA, creator: creator.publicKey })
+      .signers([creator])
+      .rpc();
+
+    let launch = await program.account.launch.fetch(launchPDA);
+    assert.ok(launch.creator.equals(creator.publicKey));
+    assert.ok(launch.pendingCreator.equals(newCreator.publicKey));
+
+    // Only the pending creator can accept
+    await expectError(
+      program.methods
+        .acceptCreator()
+        .accounts({ launch: launchPDA, newCreator: stranger.publicKey })
+        .signers([stranger])
+        .rpc(),
+      "InvalidAuthority"
+    );
+
+    await program.methods
+      .acceptCreator()
+      .accounts({ launch: launchPDA, newCreator: newCreator.publicKey })
+      .signers([newCreator])
+      .rpc();
+
+    launch = await program.account.launch.fetch(launchPDA);
+    assert.ok(launch.creator.equals(newCreator.publicKey));
+    assert.ok(launch.pendingCreator.equals(PublicKey.default));
+
+    // The old creator lost the launch settings, the new one has them
+    const newUri = "https://test.com/metadata-new-creator.json";
+    await expectError(updateUri(creator, newUri), "ConstraintHasOne");
+    await updateUri(newCreator, newUri);
+
+    launch = await program.account.launch.fetch(launchPDA);
+    assert.equal(launch.uri, newUri);
+    console.log("✓ Creator role transferred");
+  });
+
   it("Buys tokens from bonding curve", async () => {
     // This test would need a previously created launch
     // Implementation similar to create launch test